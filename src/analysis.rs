@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde_json::{json, Value};
+use crate::backend::Square;
+use crate::moves::{Action, Move, Rule};
+use crate::notation::{square_char, Position};
+use crate::posn::Posn;
+
+// what the solver makes of a position without playing a move
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub position: Position,
+    // the certain moves, or the single recommended guess when there are none
+    pub moves: Vec<Move>,
+    // chance of a mine for every blank cell, only filled in when nothing is certain
+    pub probabilities: HashMap<Posn, f64>,
+}
+
+impl Analysis {
+    pub fn guess(&self) -> Option<&Move> {
+        self.moves.iter().find(|found| found.is_guess())
+    }
+
+    fn posns(&self, action: Action) -> Vec<Posn> {
+        self.moves
+            .iter()
+            .filter(|found| found.action == action && !found.is_guess())
+            .map(|found| found.posn)
+            .collect()
+    }
+
+    pub fn mines(&self) -> Vec<Posn> {
+        self.posns(Action::Flag)
+    }
+
+    pub fn safe(&self) -> Vec<Posn> {
+        self.posns(Action::Reveal)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut probabilities: Vec<(&Posn, &f64)> = self.probabilities.iter().collect();
+        probabilities.sort_by_key(|(posn, _)| (posn.row, posn.col));
+        json!({
+            "position": self.position.to_string(),
+            "moves": self.moves.iter().map(Move::to_json).collect::<Vec<_>>(),
+            "probabilities": probabilities
+                .into_iter()
+                .map(|(posn, probability)| json!({ "row": posn.row, "col": posn.col, "probability": probability }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn coords_list(posns: &[Posn]) -> String {
+    posns.iter().map(Posn::coords).collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(guess) = self.guess() else {
+            if self.moves.is_empty() {
+                return writeln!(f, "nothing left to guess");
+            }
+            writeln!(f, "certain mines: {}", coords_list(&self.mines()))?;
+            writeln!(f, "certain safe:  {}", coords_list(&self.safe()))?;
+            for found in &self.moves {
+                writeln!(f, "  {}", found)?;
+            }
+            return Ok(());
+        };
+
+        writeln!(f, "no certain moves, chance of a mine per blank square:")?;
+        for row in 0..self.position.rows {
+            let line: String = (0..self.position.cols)
+                .map(|col| {
+                    let posn = Posn::new(row as i32, col as i32);
+                    match (self.position.square(posn), self.probabilities.get(&posn)) {
+                        (Square::Hidden, Some(probability)) => format!("{:>4.0}%", 100.0 * probability),
+                        (square, _) => format!("{:>5}", square_char(square)),
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        if let Rule::Guess { probability, .. } = guess.rule {
+            writeln!(f, "recommended guess: {} with a {:.1}% chance of a mine", guess.posn.coords(), 100.0 * probability)?;
+        }
+        writeln!(f, "  {}", guess)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let position: Position = "size 1x3\nmines 1\n1__\n".parse().unwrap();
+        let number = vec![Posn::new(0, 0)];
+        let certain = Analysis {
+            position: position.clone(),
+            moves: vec![
+                Move::new(Action::Flag, Posn::new(0, 1), Rule::Enumeration { solutions: 1 }, number.clone()),
+                Move::new(Action::Reveal, Posn::new(0, 2), Rule::Enumeration { solutions: 1 }, vec![]),
+            ],
+            probabilities: HashMap::new(),
+        };
+        assert!(certain.to_string().starts_with("certain mines: (2, 1)\ncertain safe:  (3, 1)\n  flag (2, 1): "));
+
+        let guess = Analysis {
+            moves: vec![Move::new(Action::Reveal, Posn::new(0, 2), Rule::Guess { probability: 0.5, forced: true }, number)],
+            probabilities: [(Posn::new(0, 1), 0.5), (Posn::new(0, 2), 0.5)].into_iter().collect(),
+            ..certain
+        };
+        assert!(guess.to_string().starts_with(
+            "no certain moves, chance of a mine per blank square:\n    1  50%  50%\nrecommended guess: (3, 1) with a 50.0% chance of a mine\n"
+        ));
+        assert_eq!(guess.to_json()["probabilities"][1], json!({ "row": 0, "col": 2, "probability": 0.5 }));
+        assert_eq!(guess.to_json()["moves"][0]["forced"], json!(true));
+    }
+}
@@ -0,0 +1,90 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+// same ids and classes as minesweeperonline.com, mines are placed from the seed on the first click
+const PAGE: &str = include_str!("mock_site.html");
+
+// serves the mock page on a free local port until dropped
+pub struct MockSite {
+    url: String,
+    server: JoinHandle<()>,
+}
+
+impl MockSite {
+    pub async fn start(seed: u32) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        let page = PAGE.replace("__SEED__", &seed.to_string());
+
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, page.clone()));
+            }
+        });
+        Ok(MockSite { url, server })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for MockSite {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+// answers a single request, the page for "/" and 404 for anything else
+async fn serve(mut stream: TcpStream, page: String) -> std::io::Result<()> {
+    let mut request = vec![];
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = match path {
+        "/" | "/index.html" => ("200 OK", page.as_str()),
+        _ => ("404 Not Found", "not found"),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(url: &str, path: &str) -> String {
+        let address = url.trim_start_matches("http://").trim_end_matches('/');
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, address);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serves_the_page() {
+        let site = MockSite::start(42).await.unwrap();
+        let page = get(site.url(), "/").await;
+        assert!(page.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(page.contains(r#"id="face""#));
+        assert!(page.contains("const SEED = 42;"));
+        assert!(get(site.url(), "/favicon.ico").await.starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,86 @@
+use std::future::Future;
+use crate::error::Result;
+use crate::posn::Posn;
+pub use crate::engine::{FirstClick, Square, Status};
+
+pub mod webdriver;
+mod offline;
+mod position;
+#[cfg(test)]
+mod mock_site;
+
+// everything the solver needs from a game, whether it is a website or a simulation
+// the futures are Send so a board can be played on any tokio worker thread
+pub trait Backend: Send + Sync {
+    fn rows(&self) -> usize;
+
+    fn cols(&self) -> usize;
+
+    fn mines(&self) -> usize;
+
+    // what the game promises about the first click, the site only keeps the clicked square free
+    fn first_click(&self) -> FirstClick {
+        FirstClick::Safe
+    }
+
+    // left click on a square
+    fn reveal(&mut self, posn: Posn) -> impl Future<Output = Result<()>> + Send;
+
+    // right click on a blank square
+    fn flag(&mut self, posn: Posn) -> impl Future<Output = Result<()>> + Send;
+
+    // opens every unflagged neighbor of a satisfied number
+    fn chord(&mut self, posn: Posn) -> impl Future<Output = Result<()>> + Send;
+
+    // current state of a single square
+    fn square(&self, posn: Posn) -> impl Future<Output = Result<Square>> + Send;
+
+    // current state of every square, indexed by row then column
+    fn read_board(&self) -> impl Future<Output = Result<Vec<Vec<Square>>>> + Send {
+        async move {
+            let mut squares = Vec::with_capacity(self.rows());
+            for row in 0..self.rows() {
+                let mut row_squares = Vec::with_capacity(self.cols());
+                for col in 0..self.cols() {
+                    row_squares.push(self.square(Posn::new(row as i32, col as i32)).await?);
+                }
+                squares.push(row_squares);
+            }
+            Ok(squares)
+        }
+    }
+
+    // whether the game is still going, won or lost (the face on the site)
+    fn status(&self) -> impl Future<Output = Result<Status>> + Send;
+
+    // every mine once the game is over, taken from the mines and flags it shows
+    fn mine_layout(&self) -> impl Future<Output = Result<Option<Vec<Posn>>>> + Send {
+        async move {
+            if self.status().await? == Status::Playing {
+                return Ok(None);
+            }
+            let squares = self.read_board().await?;
+            let mines = (0..self.rows())
+                .flat_map(|row| (0..self.cols()).map(move |col| Posn::new(row as i32, col as i32)))
+                .filter(|posn| {
+                    matches!(squares[posn.row as usize][posn.col as usize], Square::Mine | Square::Exploded | Square::Flagged)
+                })
+                .collect();
+            Ok(Some(mines))
+        }
+    }
+
+    // starts a new game with the same dimensions
+    fn reset(&mut self) -> impl Future<Output = Result<()>> + Send;
+
+    // starts over on a new game after the connection to the old one was lost,
+    // returns whether that is possible at all
+    fn recover(&mut self) -> impl Future<Output = Result<bool>> + Send {
+        async { Ok(false) }
+    }
+
+    // lets go of whatever the game holds on to, e.g. a browser session
+    fn close(&mut self) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
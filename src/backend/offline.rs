@@ -0,0 +1,69 @@
+use crate::backend::{Backend, FirstClick, Square, Status};
+use crate::engine::Game;
+use crate::error::{Error, Result};
+use crate::posn::Posn;
+
+// moves on a finished game fail with GameOver, everything else always succeeds
+impl Backend for Game {
+    fn rows(&self) -> usize {
+        Game::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        Game::cols(self)
+    }
+
+    fn mines(&self) -> usize {
+        Game::mines(self)
+    }
+
+    fn first_click(&self) -> FirstClick {
+        Game::first_click(self)
+    }
+
+    async fn reveal(&mut self, posn: Posn) -> Result<()> {
+        self.check_playing()?;
+        Game::reveal(self, posn);
+        Ok(())
+    }
+
+    async fn flag(&mut self, posn: Posn) -> Result<()> {
+        self.check_playing()?;
+        Game::flag(self, posn);
+        Ok(())
+    }
+
+    async fn chord(&mut self, posn: Posn) -> Result<()> {
+        self.check_playing()?;
+        Game::chord(self, posn);
+        Ok(())
+    }
+
+    async fn square(&self, posn: Posn) -> Result<Square> {
+        Ok(Game::square(self, posn))
+    }
+
+    async fn status(&self) -> Result<Status> {
+        Ok(Game::status(self))
+    }
+
+    // the engine knows the mines as soon as the first click placed them
+    async fn mine_layout(&self) -> Result<Option<Vec<Posn>>> {
+        let layout = Game::mine_layout(self);
+        Ok(if layout.is_empty() && Game::mines(self) > 0 { None } else { Some(layout) })
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        Game::reset(self);
+        Ok(())
+    }
+}
+
+impl Game {
+    fn check_playing(&self) -> Result<()> {
+        match Game::status(self) {
+            Status::Playing => Ok(()),
+            status => Err(Error::GameOver(status)),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::backend::{Backend, Square, Status};
+use crate::error::{Error, Result};
+use crate::notation::Position;
+use crate::posn::Posn;
+
+// a position knows what is shown but not where the mines are, so only flags can be placed
+impl Backend for Position {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn mines(&self) -> usize {
+        self.mines
+    }
+
+    async fn reveal(&mut self, posn: Posn) -> Result<()> {
+        Err(Error::InvalidState(format!("cannot open {} of a position without a mine layout", posn.coords())))
+    }
+
+    async fn flag(&mut self, posn: Posn) -> Result<()> {
+        if self.square(posn) == Square::Hidden {
+            self.set_square(posn, Square::Flagged);
+        }
+        Ok(())
+    }
+
+    async fn chord(&mut self, posn: Posn) -> Result<()> {
+        Err(Error::InvalidState(format!("cannot chord {} of a position without a mine layout", posn.coords())))
+    }
+
+    async fn square(&self, posn: Posn) -> Result<Square> {
+        Ok(Position::square(self, posn))
+    }
+
+    async fn read_board(&self) -> Result<Vec<Vec<Square>>> {
+        Ok(self.squares().to_vec())
+    }
+
+    async fn status(&self) -> Result<Status> {
+        Ok(Status::Playing)
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        Err(Error::InvalidState("a position cannot be restarted".to_string()))
+    }
+}
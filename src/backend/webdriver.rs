@@ -0,0 +1,369 @@
+use fantoccini::{Client, Locator};
+use fantoccini::actions::{InputSource, MouseActions, PointerAction, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT};
+use serde_json::Value;
+use tokio::time::Duration;
+use crate::backend::{Backend, Square, Status};
+use crate::difficulty::Difficulty;
+use crate::error::{Error, Result};
+use crate::info;
+use crate::posn::Posn;
+use crate::session::{self, SessionOptions};
+
+pub const SITE: &str = "https://minesweeperonline.com/";
+
+// size of the visible field and the value of the mine counter, as shown by the page
+const READ_DIMENSIONS: &str = r#"
+    let rows = 0, cols = 0;
+    for (const square of document.querySelectorAll('#game .square')) {
+        if (square.style.display === 'none') continue;
+        const [row, col] = square.id.split('_').map(Number);
+        rows = Math.max(rows, row);
+        cols = Math.max(cols, col);
+    }
+    const digits = ['mines_hundreds', 'mines_tens', 'mines_ones'].map(id => {
+        const element = document.getElementById(id);
+        const match = element && element.className.match(/time(\d)/);
+        return match ? match[1] : null;
+    });
+    const mines = digits.includes(null) ? null : Number(digits.join(''));
+    return [rows, cols, mines];
+"#;
+
+// class of every square in row-major order, called with the number of rows and columns
+const READ_BOARD: &str = r#"
+    const [rows, cols] = arguments;
+    const classes = [];
+    for (let row = 1; row <= rows; row++) {
+        for (let col = 1; col <= cols; col++) {
+            const square = document.getElementById(row + '_' + col);
+            classes.push(square ? square.className : null);
+        }
+    }
+    return classes;
+"#;
+
+// minesweeperonline.com driven through a WebDriver session
+pub struct WebDriver {
+    client: Client,
+    options: SessionOptions,
+    difficulty: Difficulty,
+    rows: usize,
+    cols: usize,
+    mines: usize,
+}
+
+impl WebDriver {
+    // starts a browser session and opens a game
+    pub async fn connect(options: SessionOptions, difficulty: Difficulty) -> Result<Self> {
+        let client = options.connect().await?;
+        let (rows, cols, mines) = match Self::open(&client, &options.site, difficulty).await {
+            Ok(dimensions) => dimensions,
+            Err(error) => {
+                session::close(client).await;
+                return Err(error);
+            }
+        };
+        Ok(WebDriver {
+            client,
+            options,
+            difficulty,
+            rows,
+            cols,
+            mines,
+        })
+    }
+
+    // loads the game page, returns its rows, columns and mines
+    async fn open(client: &Client, site: &str, difficulty: Difficulty) -> Result<(usize, usize, usize)> {
+        let game_type = match difficulty {
+            Difficulty::Beginner => "beginner",
+            Difficulty::Intermediate => "intermediate",
+            Difficulty::Expert => "expert",
+            Difficulty::Custom { .. } => "custom",
+        };
+        client.goto(&format!("{}#{}", site, game_type)).await?;
+
+        if let Difficulty::Custom { rows, cols, mines } = difficulty {
+            Self::select_custom(client, rows, cols, mines).await?;
+        }
+        client.wait().for_element(Locator::Css(".square.blank")).await?;
+        client.find(Locator::Css("#face")).await?;
+
+        // trust the page over the requested size, the site clamps custom games
+        let dimensions = client.execute(READ_DIMENSIONS, vec![]).await?;
+        let read = |index: usize| dimensions.get(index).and_then(Value::as_u64).filter(|&n| n > 0);
+
+        Ok((
+            read(0).map_or(difficulty.rows(), |n| n as usize),
+            read(1).map_or(difficulty.cols(), |n| n as usize),
+            read(2).map_or(difficulty.mines(), |n| n as usize),
+        ))
+    }
+
+    // fills in the custom game form of the options dialog
+    async fn select_custom(client: &Client, rows: usize, cols: usize, mines: usize) -> Result<()> {
+        client.find(Locator::Css("#options-link")).await?.click().await?;
+        client.wait().for_element(Locator::Css("#custom")).await?.click().await?;
+        for (id, value) in [("#custom_height", rows), ("#custom_width", cols), ("#custom_mines", mines)] {
+            let input = client.find(Locator::Css(id)).await?;
+            input.clear().await?;
+            input.send_keys(&value.to_string()).await?;
+        }
+        client.find(Locator::Css("#options input[type=submit]")).await?.click().await?;
+        Ok(())
+    }
+
+    // css selector of a square, ids look like "1_1" and start with a digit that has to be escaped
+    fn locator(posn: Posn) -> String {
+        let id = format!("{}_{}", posn.row + 1, posn.col + 1);
+        format!(r#"#\3{} {}"#, &id[..1], &id[1..])
+    }
+
+    // presses and releases the given buttons over a square
+    async fn press(&self, posn: Posn, buttons: &[u64]) -> Result<()> {
+        let element = self.client.find(Locator::Css(&Self::locator(posn))).await?;
+        let mut mouse_actions = MouseActions::new("mouse".to_string())
+            .then(PointerAction::MoveToElement {
+                element,
+                duration: Some(Duration::from_millis(100)),
+                x: 0,
+                y: 0,
+            });
+        for &button in buttons {
+            mouse_actions = mouse_actions.then(PointerAction::Down { button });
+        }
+        for &button in buttons {
+            mouse_actions = mouse_actions.then(PointerAction::Up { button });
+        }
+        self.client.perform_actions(mouse_actions).await?;
+        self.client.release_actions().await?;
+        Ok(())
+    }
+}
+
+// parses the class attribute of a square
+pub fn parse_square(attribute: &str) -> Option<Square> {
+    if let Some(&rep) = info::get_reps().get(attribute) {
+        return match rep {
+            '_' => Some(Square::Hidden),
+            'f' => Some(Square::Flagged),
+            digit => digit.to_digit(10).map(|n| Square::Open(n as u8)),
+        };
+    }
+    if attribute.contains("bombdeath") {
+        Some(Square::Exploded)
+    } else if attribute.contains("bombrevealed") {
+        Some(Square::Mine)
+    } else if attribute.contains("bombmisflagged") {
+        Some(Square::Flagged)
+    } else {
+        None
+    }
+}
+
+impl Backend for WebDriver {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn mines(&self) -> usize {
+        self.mines
+    }
+
+    async fn reveal(&mut self, posn: Posn) -> Result<()> {
+        let element = self.client.find(Locator::Css(&Self::locator(posn))).await?;
+        element.click().await?;
+        Ok(())
+    }
+
+    async fn flag(&mut self, posn: Posn) -> Result<()> {
+        self.press(posn, &[MOUSE_BUTTON_RIGHT]).await
+    }
+
+    async fn chord(&mut self, posn: Posn) -> Result<()> {
+        self.press(posn, &[MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT]).await
+    }
+
+    async fn square(&self, posn: Posn) -> Result<Square> {
+        let element = self.client.find(Locator::Css(&Self::locator(posn))).await?;
+
+        match element.attr("class").await? {
+            Some(attribute) => parse_square(&attribute)
+                .ok_or_else(|| Error::UnexpectedDom(format!("unknown square {} at {}", attribute, posn.coords()))),
+            None => Err(Error::UnexpectedDom(format!("square at {} has no class", posn.coords()))),
+        }
+    }
+
+    // one round-trip instead of a find and an attribute read per square
+    async fn read_board(&self) -> Result<Vec<Vec<Square>>> {
+        let classes = self.client.execute(READ_BOARD, vec![self.rows.into(), self.cols.into()]).await?;
+        let classes = classes
+            .as_array()
+            .filter(|classes| classes.len() == self.rows * self.cols)
+            .ok_or_else(|| Error::UnexpectedDom(format!("expected {} squares, got {}", self.rows * self.cols, classes)))?;
+
+        let mut squares = vec![Vec::with_capacity(self.cols); self.rows];
+        for (index, class) in classes.iter().enumerate() {
+            let posn = Posn::new((index / self.cols) as i32, (index % self.cols) as i32);
+            let square = class
+                .as_str()
+                .and_then(parse_square)
+                .ok_or_else(|| Error::UnexpectedDom(format!("unknown square {} at {}", class, posn.coords())))?;
+            squares[posn.row as usize].push(square);
+        }
+        Ok(squares)
+    }
+
+    async fn status(&self) -> Result<Status> {
+        let face = self.client.find(Locator::Css("#face")).await?;
+        let attribute = face.attr("class").await?.unwrap_or_default();
+        if attribute.contains("facewin") {
+            Ok(Status::Won)
+        } else if attribute.contains("facedead") {
+            Ok(Status::Lost)
+        } else {
+            Ok(Status::Playing)
+        }
+    }
+
+    // a flag on a safe square reads as a flag, only its class tells it apart
+    async fn mine_layout(&self) -> Result<Option<Vec<Posn>>> {
+        if self.status().await? == Status::Playing {
+            return Ok(None);
+        }
+        let classes = self.client.execute(READ_BOARD, vec![self.rows.into(), self.cols.into()]).await?;
+        let mines = classes
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, class)| {
+                let class = class.as_str().unwrap_or_default();
+                ["bombdeath", "bombrevealed", "bombflagged"].iter().any(|mine| class.contains(mine))
+            })
+            .map(|(index, _)| Posn::new((index / self.cols) as i32, (index % self.cols) as i32))
+            .collect();
+        Ok(Some(mines))
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.client.find(Locator::Css("#face")).await?.click().await?;
+        Ok(())
+    }
+
+    // a fresh session on a fresh page, the old one is closed if it still answers
+    async fn recover(&mut self) -> Result<bool> {
+        let client = self.options.connect().await?;
+        let dimensions = match Self::open(&client, &self.options.site, self.difficulty).await {
+            Ok(dimensions) => dimensions,
+            Err(error) => {
+                session::close(client).await;
+                return Err(error);
+            }
+        };
+        if dimensions != (self.rows, self.cols, self.mines) {
+            session::close(client).await;
+            return Err(Error::UnexpectedDom(format!(
+                "the new session shows a {}x{} board with {} mines instead of {}x{} with {}",
+                dimensions.0, dimensions.1, dimensions.2, self.rows, self.cols, self.mines
+            )));
+        }
+        let old = std::mem::replace(&mut self.client, client);
+        session::close(old).await;
+        Ok(true)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.client.clone().close().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock_site::MockSite;
+    use crate::board::Board;
+    use crate::logging::Logger;
+
+    // the tests against the mock site need a chromedriver, run them with
+    //   chromedriver --port=9515 & cargo test -- --ignored
+    // or point WEBDRIVER at another endpoint
+    async fn connect(site: &MockSite, difficulty: Difficulty) -> WebDriver {
+        let options = SessionOptions {
+            webdriver: std::env::var("WEBDRIVER").unwrap_or_else(|_| "http://localhost:9515".to_string()),
+            site: site.url().to_string(),
+            headless: true,
+            window_size: None,
+            browser_args: vec!["--no-sandbox".to_string()],
+        };
+        WebDriver::connect(options, difficulty).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_mock_site_squares() {
+        let site = MockSite::start(1).await.unwrap();
+        let mut backend = connect(&site, Difficulty::Beginner).await;
+        assert_eq!((backend.rows(), backend.cols(), backend.mines()), (9, 9, 10));
+
+        backend.reveal(Posn::new(4, 4)).await.unwrap();
+        let squares = backend.read_board().await.unwrap();
+        assert!(matches!(squares[4][4], Square::Open(_)));
+
+        let hidden = (0..81).map(|index| Posn::new(index / 9, index % 9)).find(|posn| {
+            squares[posn.row as usize][posn.col as usize] == Square::Hidden
+        });
+        if let Some(hidden) = hidden {
+            backend.flag(hidden).await.unwrap();
+            assert_eq!(backend.square(hidden).await.unwrap(), Square::Flagged);
+        }
+        // both ways of reading agree
+        let squares = backend.read_board().await.unwrap();
+        for posn in [Posn::new(0, 0), Posn::new(4, 4), Posn::new(8, 8)] {
+            assert_eq!(backend.square(posn).await.unwrap(), squares[posn.row as usize][posn.col as usize]);
+        }
+        backend.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_play_on_mock_site() {
+        let site = MockSite::start(7).await.unwrap();
+        for difficulty in [Difficulty::Beginner, Difficulty::Custom { rows: 10, cols: 12, mines: 20 }] {
+            let backend = connect(&site, difficulty).await;
+            assert_eq!((backend.rows(), backend.cols(), backend.mines()), (difficulty.rows(), difficulty.cols(), difficulty.mines()));
+
+            let mut board = Board::new(Logger::quiet(), true, backend);
+            for _ in 0..2 {
+                let report = board.play().await.unwrap();
+                assert_ne!(report.status, Status::Playing);
+                let layout = board.recording().mine_layout.clone().unwrap();
+                assert_eq!(layout.len(), difficulty.mines());
+                board.reset_game().await.unwrap();
+            }
+            board.close().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_locator() {
+        assert_eq!(WebDriver::locator(Posn::new(0, 0)), r#"#\31 _1"#);
+        assert_eq!(WebDriver::locator(Posn::new(11, 29)), r#"#\31 2_30"#);
+        assert_eq!(WebDriver::locator(Posn::new(23, 4)), r#"#\32 4_5"#);
+    }
+
+    #[test]
+    fn test_parse_square() {
+        assert_eq!(parse_square("square blank"), Some(Square::Hidden));
+        assert_eq!(parse_square("square bombflagged"), Some(Square::Flagged));
+        assert_eq!(parse_square("square open3"), Some(Square::Open(3)));
+        assert_eq!(parse_square("square bombdeath"), Some(Square::Exploded));
+        assert_eq!(parse_square("square bombrevealed"), Some(Square::Mine));
+        assert_eq!(parse_square("square"), None);
+    }
+}
@@ -0,0 +1,124 @@
+const WORD: usize = u64::BITS as usize;
+
+// fixed-size set of small indices, one bit each
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> Self {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD)],
+            capacity,
+        }
+    }
+
+    // every index below the capacity
+    pub fn full(capacity: usize) -> Self {
+        let mut set = BitSet::new(capacity);
+        set.words.iter_mut().for_each(|word| *word = u64::MAX);
+        if !capacity.is_multiple_of(WORD) {
+            if let Some(last) = set.words.last_mut() {
+                *last = (1 << (capacity % WORD)) - 1;
+            }
+        }
+        set
+    }
+
+    // returns whether the index was newly added
+    pub fn insert(&mut self, index: usize) -> bool {
+        assert!(index < self.capacity, "index {} out of range for a set of {}", index, self.capacity);
+        let (word, bit) = (index / WORD, 1 << (index % WORD));
+        let added = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        added
+    }
+
+    // returns whether the index was present
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index >= self.capacity {
+            return false;
+        }
+        let (word, bit) = (index / WORD, 1 << (index % WORD));
+        let present = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        present
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.capacity && self.words[index / WORD] & (1 << (index % WORD)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|word| *word = 0);
+    }
+
+    // removes every index that is also in the other set
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (word, &other_word) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other_word;
+        }
+    }
+
+    // indices in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(index * WORD + bit)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = BitSet::new(130);
+        assert!(set.is_empty());
+        assert!(set.insert(0));
+        assert!(set.insert(64));
+        assert!(set.insert(129));
+        assert!(!set.insert(64));
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(129));
+        assert!(!set.contains(128));
+        assert!(set.remove(64));
+        assert!(!set.remove(64));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 129]);
+    }
+
+    #[test]
+    fn test_full_and_difference() {
+        let mut set = BitSet::full(70);
+        assert_eq!(set.len(), 70);
+        assert!(!set.contains(70));
+
+        let mut other = BitSet::new(70);
+        for index in [1, 3, 69] {
+            other.insert(index);
+        }
+        set.difference_with(&other);
+        assert_eq!(set.len(), 67);
+        assert!(!set.contains(3));
+        assert_eq!(set.iter().take(3).collect::<Vec<_>>(), vec![0, 2, 4]);
+    }
+}
@@ -1,260 +1,815 @@
-use fantoccini::{Client, Locator};
-use fantoccini::error::CmdError;
-use std::collections::HashSet;
-use rand::seq::IteratorRandom;
+use std::collections::{HashMap, HashSet};
+use rand::rngs::StdRng;
+use rand::RngCore;
 use tokio::time::Instant;
-use crate::cell_wrapper::CellWrapper;
-
-pub struct Board {
-    pub log: bool,
+use crate::backend::{Backend, Square, Status};
+use crate::bitset::BitSet;
+use crate::difficulty::Difficulty;
+use crate::error::{Error, Result};
+use crate::grid::{Grid, Settled};
+use crate::logging::{Level, Logger};
+use crate::moves::{Action, Move, Rule};
+use crate::notation::Position;
+use crate::posn::Posn;
+use crate::analysis::Analysis;
+use crate::recording::Recording;
+use crate::seed::{self, Stream};
+use serde_json::json;
+use crate::solver::{fifty, linear, mine_count, probability, Constraint};
+use crate::solver::fifty::Region;
+use crate::solver::frontier::{Deductions, Frontier};
+use crate::solver::guess::{GuessContext, GuessPolicy, Informed};
+use crate::solver::opening::Opening;
+use crate::stats::{GameReport, LossCause};
+
+pub struct Board<B: Backend> {
+    pub logger: Logger,
     pub mark_flags: bool,
     pub rows: usize,
     pub cols: usize,
     mines: usize,
-    blank: HashSet<CellWrapper>,
-    bombs: HashSet<CellWrapper>,
-    numbers: HashSet<CellWrapper>,
-    workset: HashSet<CellWrapper>,
-    matrix: Vec<Vec<CellWrapper>>,
-    client: Client,
+    // cells and the workset are indexed the same way, row by row
+    grid: Grid,
+    workset: BitSet,
+    backend: B,
+    policy: Box<dyn GuessPolicy + Send>,
+    opening: Opening,
+    // every game draws its own seed from here, its guesses follow it
+    seeds: StdRng,
+    rng: StdRng,
+    started: Instant,
+    guesses: usize,
+    clicks: usize,
+    // whether the latest click was a guess, to tell bad luck from solver bugs
+    last_reveal_was_guess: bool,
+    recording: Recording,
 }
 
-impl Board {
-    pub async fn new(log: bool, mark_flags: bool, client: Client) -> Result<Self, fantoccini::error::CmdError> {
-        let start_time = Instant::now();
-
-        let rows = 16;
-        let cols = 30;
-        let mines = 99;
-        let link = "https://minesweeperonline.com/";
-
-        client.goto(link).await?;
-        client.wait().for_element(Locator::Css(".square.blank")).await?;
+// what a step does next
+enum Next {
+    Guess { probabilities: HashMap<Posn, f64>, forced: bool },
+    Moves { to_flag: Vec<Move>, to_reveal: Vec<Move> },
+}
 
-        let face_locator = "#face".to_string();
-        client.find(Locator::Css(&face_locator)).await?;
+impl<B: Backend> Board<B> {
+    pub fn new(logger: Logger, mark_flags: bool, backend: B) -> Self {
+        let start_time = Instant::now();
+        let (rows, cols) = (backend.rows(), backend.cols());
+        let size = rows * cols;
+        let seed = rand::random();
 
         let mut board = Board {
-            log,
+            logger,
             mark_flags,
             rows,
             cols,
-            mines,
-            blank: HashSet::new(),
-            bombs: HashSet::new(),
-            numbers: HashSet::new(),
-            workset: HashSet::new(),
-            matrix: vec![],
-            client: client.clone(),
+            mines: backend.mines(),
+            recording: Recording::new(rows, cols, backend.mines()),
+            grid: Grid::new(rows, cols),
+            workset: BitSet::new(size),
+            backend,
+            policy: Box::new(Informed),
+            opening: Opening::Policy,
+            seeds: seed::rng(seed, Stream::Games),
+            rng: seed::rng(seed, Stream::Guesses),
+            started: start_time,
+            guesses: 0,
+            clicks: 0,
+            last_reveal_was_guess: false,
         };
-        
-        board.init_fields_and_cells().await?;
-
-        if log {
-            let elapsed = start_time.elapsed();
-            println!(
-                "\t{:?} seconds to initialize a board with {} cells",
-                elapsed.as_secs_f32(),
-                board.rows * board.cols
-            );
-        }
 
-        Ok(board)
+        board.recording.seed = Some(seed);
+
+        let (seconds, cells) = (start_time.elapsed().as_secs_f64(), rows * cols);
+        logger.log(
+            Level::Trace,
+            "board",
+            format_args!("{:.3} seconds to initialize a board with {} cells", seconds, cells),
+            || json!({ "seconds": seconds, "cells": cells }),
+        );
+        board
     }
 
-    pub async fn play(&mut self) -> Result<(), CmdError> {
-        while !self.blank.is_empty() {
-            if let Ok(_) = self.client.find(Locator::Css("div#face.facewin")).await {
-                println!("Game won!");
-                return Ok(());
+    // replaces the policy used when no cell is certain
+    pub fn set_policy(&mut self, policy: Box<dyn GuessPolicy + Send>) {
+        self.policy = policy;
+    }
+
+    // replaces how the first click is picked, auto is settled by the board's size and the backend's first click rule
+    pub fn set_opening(&mut self, opening: Opening) {
+        let difficulty = Difficulty::of(self.rows, self.cols, self.mines);
+        self.opening = opening.resolve(difficulty, self.backend.first_click());
+    }
+
+    pub fn opening(&self) -> Opening {
+        self.opening
+    }
+
+    // the current game guesses from this seed, later games from seeds drawn from it
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seeds = seed::rng(seed, Stream::Games);
+        self.seed_game(seed);
+    }
+
+    // a game's own seed is enough to play it again without the ones before it
+    fn seed_game(&mut self, seed: u64) {
+        self.rng = seed::rng(seed, Stream::Guesses);
+        self.recording.seed = Some(seed);
+    }
+
+    // plays the current game until it is won or lost
+    pub async fn play(&mut self) -> Result<GameReport> {
+        while !self.grid.blank().is_empty() {
+            match self.backend.status().await? {
+                Status::Won => return self.finish(Status::Won).await,
+                Status::Lost => return self.finish(Status::Lost).await,
+                Status::Playing => {}
             }
 
-            let to_flag = self.get_cells_to_flag();
-            if !to_flag.is_empty() {
-                self.log_action("flag".to_string());
-                self.flag_all(to_flag).await;
-            } else {
-                let to_reveal = self.get_cells_to_reveal();
+            match self.step().await {
+                Err(Error::GameOver(status)) => return self.finish(status).await,
+                result => result?,
+            }
+        }
+        let status = self.backend.status().await?;
+        self.finish(status).await
+    }
+
+    // the current game so far, complete with the mines once play returned
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    async fn finish(&mut self, status: Status) -> Result<GameReport> {
+        let mine_layout = self.backend.mine_layout().await?;
+        self.recording.finish(status, mine_layout);
+        self.recording.policy = self.policy.name().to_string();
+        self.recording.opening = self.opening.name().to_string();
+        self.recording.flags = self.mark_flags;
+
+        let report = self.report(status);
+        self.logger.log(
+            Level::Info,
+            "game",
+            format_args!(
+                "game {} after {:.3}s, {:.0}% opened, {} guesses, {} clicks",
+                status.name(),
+                report.duration.as_secs_f64(),
+                100.0 * report.progress,
+                report.guesses,
+                report.clicks
+            ),
+            || report.to_json(),
+        );
+        Ok(report)
+    }
+
+    // plays the next batch of moves
+    async fn step(&mut self) -> Result<()> {
+        if self.grid.blank().len() == self.grid.len() && self.open().await? {
+            return Ok(());
+        }
+
+        let constraints = self.frontier_constraints()?;
+        match self.next_moves(&constraints)? {
+            Next::Guess { probabilities, forced } => self.guess(&probabilities, &constraints, forced).await,
+            Next::Moves { to_flag, to_reveal } => {
+                self.flag_all(to_flag).await?;
                 if !to_reveal.is_empty() {
-                    self.log_action("reveal".to_string());
-                    self.reveal_all(to_reveal).await;
-                } else {
-                    self.log_action("random".to_string());
-                    self.reveal_random().await;
+                    self.reveal_all(to_reveal).await?;
                 }
+                Ok(())
             }
         }
+    }
+
+    // picks the next batch of moves without clicking anything, every rule shares one enumeration of the frontier
+    fn next_moves(&mut self, constraints: &[Constraint]) -> Result<Next> {
+        let frontier = Frontier::new(constraints);
+
+        // coin flips are guessed right away so a failure costs as little time as possible
+        if let Some(region) = self.forced_region(&frontier) {
+            return Ok(Next::Guess { probabilities: region.probabilities, forced: true });
+        }
+
+        let to_flag = self.get_cells_to_flag();
+        if !to_flag.is_empty() {
+            return Ok(Next::Moves { to_flag, to_reveal: vec![] });
+        }
+
+        let to_reveal = self.get_cells_to_reveal()?;
+        if !to_reveal.is_empty() {
+            return Ok(Next::Moves { to_flag: vec![], to_reveal });
+        }
+
+        // row reduction is cheap, the enumeration only runs when it finds nothing
+        let (mut to_flag, mut to_reveal) = self.linear_moves(constraints)?;
+        if to_flag.is_empty() && to_reveal.is_empty() {
+            (to_flag, to_reveal) = self.enumeration_moves(&frontier)?;
+        }
+        // components too large to enumerate
+        #[cfg(feature = "sat")]
+        if to_flag.is_empty() && to_reveal.is_empty() {
+            (to_flag, to_reveal) = self.sat_moves(&frontier)?;
+        }
+        if to_flag.is_empty() && to_reveal.is_empty() {
+            (to_flag, to_reveal) = self.mine_count_moves(&frontier);
+        }
+        if to_flag.is_empty() && to_reveal.is_empty() {
+            return Ok(Next::Guess { probabilities: self.probabilities(&frontier), forced: false });
+        }
+        Ok(Next::Moves { to_flag, to_reveal })
+    }
+
+    fn report(&self, status: Status) -> GameReport {
+        let safe_cells = (self.rows * self.cols - self.mines).max(1);
+        let loss = match status {
+            Status::Lost if self.last_reveal_was_guess => Some(LossCause::Guess),
+            Status::Lost => Some(LossCause::Deduction),
+            _ => None,
+        };
+
+        GameReport {
+            status,
+            duration: self.started.elapsed(),
+            guesses: self.guesses,
+            clicks: self.clicks,
+            loss,
+            progress: if status == Status::Won { 1.0 } else { self.grid.numbers().len() as f64 / safe_cells as f64 },
+        }
+    }
+
+    pub async fn reset_game(&mut self) -> Result<()> {
+        self.logger.log(Level::Debug, "reset", "reset", || json!({}));
+        self.backend.reset().await?;
+        self.clear();
         Ok(())
     }
 
-    pub async fn reset_game(&mut self) {
-        self.log_action("reset".to_string());
-        self.client.find(Locator::Css("#face")).await.unwrap().click().await.unwrap();
-        self.blank.clear();
-        self.bombs.clear();
-        self.numbers.clear();
-        self.workset.clear();
-        for row in &mut self.matrix {
-            for cell in row {
-                cell.0.borrow_mut().reset();
-                self.blank.insert(cell.clone());
-            }
+    // starts over on a new game after the backend lost the current one, returns whether it could
+    pub async fn recover(&mut self) -> Result<bool> {
+        if !self.backend.recover().await? {
+            return Ok(false);
         }
+        self.clear();
+        Ok(true)
+    }
+
+    pub async fn close(&mut self) -> Result<()> {
+        self.backend.close().await
+    }
+
+    // forgets everything about the previous game
+    fn clear(&mut self) {
+        self.started = Instant::now();
+        self.guesses = 0;
+        self.clicks = 0;
+        self.last_reveal_was_guess = false;
+        self.recording = Recording::new(self.rows, self.cols, self.mines);
+        let seed = self.seeds.next_u64();
+        self.seed_game(seed);
+        self.grid.reset();
+        self.workset.clear();
     }
 
-    pub async fn reveal_random(&mut self) -> Result<(), fantoccini::error::CmdError> {
-        // filter blank cells with no non zero neighbors
-        let no_numbers: HashSet<_> = self.blank.iter()
-            .filter(|&&ref cell| cell.0.borrow().non_zero_number_neighbors().is_empty())
-            .cloned()
-            .collect();
-
-        // lowest probability of being a bomb
-        let mut lowest_prob: f64;
-        let mut random_cell = if no_numbers.is_empty() {
-            lowest_prob = 1.0;
-            self.blank.iter().choose(&mut rand::thread_rng()).unwrap().clone()
-        } else {
-            lowest_prob = (self.mines - self.bombs.len()) as f64 / no_numbers.len() as f64;
-            no_numbers.iter().choose(&mut rand::thread_rng()).unwrap().clone()
+    // clicks the square of the opening, returns whether it picked one
+    async fn open(&mut self) -> Result<bool> {
+        let Some(posn) = self.opening.square(self.rows, self.cols, &mut self.rng) else {
+            return Ok(false);
         };
+        let opening = Move::new(Action::Reveal, posn, Rule::Opening { opening: self.opening }, vec![]);
+        self.reveal_guess(opening).await?;
+        Ok(true)
+    }
 
-        // check workset for probabilities
-        for cell in &self.workset {
-            let blank_neighbors = cell.0.borrow().blank_neighbors();
-            if !blank_neighbors.is_empty() {
-                let prob = cell.0.borrow().bombs_remaining() as f64 / blank_neighbors.len() as f64;
-                if prob < lowest_prob {
-                    lowest_prob = prob;
-                    random_cell = blank_neighbors.iter().choose(&mut rand::thread_rng()).unwrap().clone().clone();
-                }
+    // a region no other reveal can resolve, one of its cells has to be guessed
+    fn forced_region(&self, frontier: &Frontier) -> Option<Region> {
+        let hidden: HashSet<Posn> = self.posns(self.grid.blank()).collect();
+        fifty::forced_guesses(frontier, &hidden, self.rows, self.cols).into_iter().next()
+    }
+
+    // lets the policy pick one of the given cells
+    fn choose_guess(&mut self, probabilities: &HashMap<Posn, f64>, constraints: &[Constraint], forced: bool) -> Option<Move> {
+        let flagged: HashSet<Posn> = self.posns(self.grid.bombs()).collect();
+        let context = GuessContext {
+            rows: self.rows,
+            cols: self.cols,
+            probabilities,
+            constraints,
+            flagged: &flagged,
+        };
+        let posn = self.policy.choose(&context, &mut self.rng)?;
+        let probability = probabilities.get(&posn).copied().unwrap_or(0.0);
+        Some(Move::new(Action::Reveal, posn, Rule::Guess { probability, forced }, self.numbers_around(posn)))
+    }
+
+    // lets the policy pick one of the given cells and reveals it
+    async fn guess(&mut self, probabilities: &HashMap<Posn, f64>, constraints: &[Constraint], forced: bool) -> Result<()> {
+        let Some(guess) = self.choose_guess(probabilities, constraints, forced) else {
+            return Ok(());
+        };
+        self.reveal_guess(guess).await
+    }
+
+    // a click that may hit a mine, counted as a guess
+    async fn reveal_guess(&mut self, guess: Move) -> Result<()> {
+        self.log_move(&guess);
+        self.guesses += 1;
+        self.last_reveal_was_guess = true;
+
+        self.backend.reveal(guess.posn).await?;
+        self.clicks += 1;
+        self.recording.push(self.started.elapsed(), guess);
+        self.update_board().await
+    }
+
+    // chance of holding a mine for every blank cell using the global mine count, exact unless the frontier is too large
+    fn probabilities(&self, frontier: &Frontier) -> HashMap<Posn, f64> {
+        let constrained = constrained_cells(frontier);
+        let blank: Vec<Posn> = self.posns(self.grid.blank()).collect();
+        let unconstrained = blank.iter().filter(|posn| !constrained.contains(posn)).count();
+
+        let probabilities = probability::compute(frontier, unconstrained, self.mines_left());
+        blank.into_iter().map(|posn| (posn, probabilities.get(posn))).collect()
+    }
+
+    // mines not flagged yet
+    fn mines_left(&self) -> usize {
+        self.mines.saturating_sub(self.grid.bombs().len())
+    }
+
+    // every certain move and, when there is none, the guess play would make, without clicking anything
+    pub fn analyze(&mut self) -> Result<Analysis> {
+        // the frontier goes first, the simple rules drop satisfied numbers from the workset
+        let constraints = self.frontier_constraints()?;
+        let frontier = Frontier::new(&constraints);
+        let (linear_flags, linear_reveals) = self.linear_moves(&constraints)?;
+        let (enumeration_flags, enumeration_reveals) = self.enumeration_moves(&frontier)?;
+
+        let mut moves = self.get_cells_to_flag();
+        for found in self.get_cells_to_reveal()? {
+            if found.action == Action::Chord {
+                let index = self.grid.index(found.posn);
+                moves.extend(self.grid.blank_neighbors(index).into_iter().map(|neighbor| {
+                    Move::new(Action::Reveal, self.grid.posn(neighbor), Rule::Count, vec![found.posn])
+                }));
+            } else {
+                moves.push(found);
             }
         }
+        // the simple rules explain a cell more plainly than row reduction, and that more plainly than the enumeration
+        let mut seen = BitSet::new(self.grid.len());
+        moves.extend(linear_flags.into_iter().chain(linear_reveals));
+        moves.extend(enumeration_flags.into_iter().chain(enumeration_reveals));
+        #[cfg(feature = "sat")]
+        {
+            let (sat_flags, sat_reveals) = self.sat_moves(&frontier)?;
+            moves.extend(sat_flags.into_iter().chain(sat_reveals));
+        }
+        let (count_flags, count_reveals) = self.mine_count_moves(&frontier);
+        moves.extend(count_flags.into_iter().chain(count_reveals));
+        moves.retain(|found| seen.insert(self.grid.index(found.posn)));
+        moves.sort_by_key(|found| (found.action != Action::Flag, found.posn.row, found.posn.col));
+
+        let mut probabilities = HashMap::new();
+        if moves.is_empty() {
+            probabilities = self.probabilities(&frontier);
+            let guess = match self.forced_region(&frontier) {
+                Some(region) => self.choose_guess(&region.probabilities, &constraints, true),
+                None => self.choose_guess(&probabilities, &constraints, false),
+            };
+            moves.extend(guess);
+        }
+
+        Ok(Analysis {
+            position: self.position(),
+            moves,
+            probabilities,
+        })
+    }
+
+    // what the solver currently knows, in the text notation
+    pub fn position(&self) -> Position {
+        let mut position = Position::new(self.rows, self.cols, self.mines);
+        for index in self.grid.bombs().iter() {
+            let cell = self.grid.cell(index);
+            // mines shown as such stay that way, the ones we flagged or found flagged are flags
+            let square = if cell.square == Square::Mine { Square::Mine } else { Square::Flagged };
+            position.set_square(cell.posn, square);
+        }
+        for index in self.grid.numbers().iter() {
+            let cell = self.grid.cell(index);
+            position.set_square(cell.posn, cell.square);
+        }
+        position
+    }
 
-        random_cell.0.borrow().click().await?;
-        self.update_from(vec![random_cell].into_iter().collect()).await?;
+    // guesses name the policy that picked them
+    fn log_move(&self, found: &Move) {
+        let policy = found.is_guess().then(|| self.policy.name());
+        self.logger.log(
+            Level::Debug,
+            "move",
+            format_args!("{}{}", found, policy.map(|name| format!(" ({})", name)).unwrap_or_default()),
+            || {
+                let mut fields = found.to_json();
+                if let Some(name) = policy {
+                    fields["policy"] = json!(name);
+                }
+                fields
+            },
+        );
+    }
 
+    async fn flag_all(&mut self, to_flag: Vec<Move>) -> Result<()> {
+        for found in to_flag {
+            self.log_move(&found);
+            let index = self.grid.index(found.posn);
+            self.grid.flag(index)?;
+            if self.mark_flags {
+                self.backend.flag(found.posn).await?;
+                self.clicks += 1;
+            }
+            self.recording.push(self.started.elapsed(), found);
+        }
         Ok(())
     }
 
-    pub async fn init_fields_and_cells(&mut self) -> Result<(), fantoccini::error::CmdError> {
-        for row in 0..self.rows {
-            let mut row_vec = vec![];
-            for col in 0..self.cols {
-                let cell = CellWrapper::with_params(row as i32, col as i32, self.client.clone());
-                row_vec.push(cell.clone());
-                self.blank.insert(cell);
+    // reads every square at once and applies the ones that changed since the last read
+    pub async fn update_board(&mut self) -> Result<()> {
+        let start_time = Instant::now();
+        let squares = self.backend.read_board().await?;
+
+        let mut updated_cells = vec![];
+        let mut flagged_cells = vec![];
+        let blank: Vec<usize> = self.grid.blank().iter().collect();
+        for index in blank {
+            let posn = self.grid.posn(index);
+            let square = squares
+                .get(posn.row as usize)
+                .and_then(|row| row.get(posn.col as usize))
+                .copied()
+                .ok_or_else(|| Error::UnexpectedDom(format!("no square read at {}", posn.coords())))?;
+            let (updated, boom) = self.grid.cell_mut(index).update(square);
+
+            if boom {
+                self.logger.log(Level::Info, "boom", format_args!("BOOM! at {}", posn.coords()), || {
+                    json!({ "row": posn.row, "col": posn.col })
+                });
+                return Ok(());
+            } else if updated {
+                updated_cells.push(index);
+            } else if matches!(square, Square::Flagged | Square::Mine) {
+                // flagged by someone else or shown as a mine, e.g. in a loaded position
+                flagged_cells.push(index);
             }
-            self.matrix.push(row_vec);
         }
 
-        let matrix_snapshot = self.matrix.clone();
-        for row in &mut self.matrix {
-            for cell in row {
-                let neighbors: HashSet<CellWrapper> = cell.borrow().neighbors_posns(self.rows as i32, self.cols as i32)
-                    .into_iter()
-                    .map(|pos| matrix_snapshot[pos.row as usize][pos.col as usize].clone())
-                    .collect();
-                cell.borrow_mut().assign_neighbors(neighbors);
+        for &index in &flagged_cells {
+            self.grid.flag(index)?;
+        }
+
+        // only once every number is known can we tell which ones still border blank cells
+        for &index in &updated_cells {
+            self.grid.mark_number(index)?;
+        }
+        for &index in &updated_cells {
+            if self.grid.should_add_to_workset(index) {
+                self.workset.insert(index);
             }
         }
 
+        let (seconds, cells) = (start_time.elapsed().as_secs_f64(), updated_cells.len());
+        self.logger.log(
+            Level::Trace,
+            "update",
+            format_args!("{:.3} seconds to update {} cells", seconds, cells),
+            || json!({ "seconds": seconds, "cells": cells }),
+        );
+
         Ok(())
     }
 
-    fn log_action(&self, action: String) {
-        if self.log {
-            println!("{}", action);
+    // plays the given chords and reveals, then reads the board once
+    async fn reveal_all(&mut self, to_reveal: Vec<Move>) -> Result<()> {
+        self.last_reveal_was_guess = false;
+        for found in to_reveal {
+            self.log_move(&found);
+            match found.action {
+                Action::Chord => self.backend.chord(found.posn).await?,
+                _ => self.backend.reveal(found.posn).await?,
+            }
+            self.clicks += 1;
+            self.recording.push(self.started.elapsed(), found);
         }
+        self.update_board().await
     }
 
-    async fn flag_all(&mut self, to_flag: HashSet<CellWrapper>) {
-        for cell in to_flag {
-            cell.flag(self.mark_flags).await.unwrap();
-            self.blank.remove(&cell);
-            self.bombs.insert(cell);
+    // one constraint per number in the workset over its blank neighbors
+    pub fn frontier_constraints(&self) -> Result<Vec<Constraint>> {
+        let mut constraints = vec![];
+        for index in self.workset.iter() {
+            let blank_neighbors = self.grid.blank_neighbors(index);
+            if blank_neighbors.is_empty() {
+                continue;
+            }
+            let remaining = self.grid.bombs_remaining(index);
+            if remaining < 0 || remaining as usize > blank_neighbors.len() {
+                return Err(Error::Contradiction(format!(
+                    "{} needs {} more mines among {} blank neighbors",
+                    self.grid.posn(index).coords(),
+                    remaining,
+                    blank_neighbors.len()
+                )));
+            }
+            let cells = blank_neighbors.into_iter().map(|neighbor| self.grid.posn(neighbor)).collect();
+            constraints.push(Constraint::new(cells, remaining as usize));
         }
+        Ok(constraints)
     }
 
-    pub async fn update_from(&mut self, mut workset: HashSet<CellWrapper>) -> Result<(), CmdError> {
-        let start_time = Instant::now();
-        let mut counter = 0;
-        let mut visited: HashSet<CellWrapper> = HashSet::new();
+    fn posns<'a>(&'a self, set: &'a BitSet) -> impl Iterator<Item = Posn> + 'a {
+        set.iter().map(|index| self.grid.posn(index))
+    }
 
-        // dfs approach
-        while !workset.is_empty() {
-            // pop first element
-            let popped = workset.iter().next().cloned().unwrap();
-            workset.remove(&popped);
+    // numbers next to a cell, the ones a deduction about it rests on
+    fn numbers_around(&self, posn: Posn) -> Vec<Posn> {
+        let index = self.grid.index(posn);
+        self.grid.non_zero_number_neighbors(index).into_iter().map(|neighbor| self.grid.posn(neighbor)).collect()
+    }
 
-            // check if the cell has been visited
-            if !visited.contains(&popped) {
-                visited.insert(popped.clone());
+    // a move made because of the number at the given index
+    fn settled_move(&self, action: Action, index: usize, (cell, other): Settled) -> Move {
+        let (rule, numbers) = match other {
+            Some(other) => (Rule::Subset, vec![self.grid.posn(index), self.grid.posn(other)]),
+            None => (Rule::Count, vec![self.grid.posn(index)]),
+        };
+        Move::new(action, self.grid.posn(cell), rule, numbers)
+    }
 
-                let (updated, boom) = popped.0.borrow_mut().update().await?;
+    // flags for the mines a solver found and reveals for the safe cells, each resting on the numbers around it
+    fn deduction_moves(&self, deductions: &Deductions, rule: impl Fn(Posn) -> Rule) -> (Vec<Move>, Vec<Move>) {
+        let settled = |action: Action, posn: Posn| Move::new(action, posn, rule(posn), self.numbers_around(posn));
+        (
+            deductions.mines.iter().map(|&posn| settled(Action::Flag, posn)).collect(),
+            deductions.safe.iter().map(|&posn| settled(Action::Reveal, posn)).collect(),
+        )
+    }
 
-                if boom {
-                    self.reset_game().await;
-                    return Ok(());
-                } else if updated {
-                    workset.extend(popped.0.borrow().blank_neighbors().iter().cloned());
+    // cells row reduction of the frontier's numbers settles
+    fn linear_moves(&self, constraints: &[Constraint]) -> Result<(Vec<Move>, Vec<Move>)> {
+        Ok(self.deduction_moves(&linear::solve(constraints)?, |_| Rule::Linear))
+    }
 
-                    self.blank.remove(&popped);
-                    self.numbers.insert(popped.clone());
+    // cells every mine layout of their frontier component agrees on
+    fn enumeration_moves(&self, frontier: &Frontier) -> Result<(Vec<Move>, Vec<Move>)> {
+        let deductions = frontier.deductions()?;
+        Ok(self.deduction_moves(&deductions, |posn| Rule::Enumeration {
+            solutions: deductions.solutions.get(&posn).copied().unwrap_or(0),
+        }))
+    }
 
-                    if popped.0.borrow().should_add_to_workset() {
-                        self.workset.insert(popped);
-                    }
+    // cells of components too large to enumerate, settled by asking for one mine layout at a time
+    #[cfg(feature = "sat")]
+    fn sat_moves(&self, frontier: &Frontier) -> Result<(Vec<Move>, Vec<Move>)> {
+        Ok(self.deduction_moves(&crate::solver::sat::solve(frontier.unenumerated())?, |_| Rule::Sat))
+    }
+
+    // cells the mine counter settles, on the frontier and off it
+    fn mine_count_moves(&self, frontier: &Frontier) -> (Vec<Move>, Vec<Move>) {
+        let constrained = constrained_cells(frontier);
+        let unconstrained: Vec<Posn> = self.posns(self.grid.blank()).filter(|posn| !constrained.contains(posn)).collect();
+        let mines = self.mines_left();
 
-                    counter += 1;
+        let deductions = mine_count::solve(frontier, &unconstrained, mines);
+        self.deduction_moves(&deductions, |_| Rule::MineCount { mines })
+    }
+
+    fn get_cells_to_flag(&self) -> Vec<Move> {
+        let mut to_flag = vec![];
+        let mut seen = BitSet::new(self.grid.len());
+        for index in self.workset.iter() {
+            for settled in self.grid.get_neighbors_to_flag(index) {
+                if seen.insert(settled.0) {
+                    to_flag.push(self.settled_move(Action::Flag, index, settled));
                 }
             }
         }
+        to_flag
+    }
 
-        let elapsed = start_time.elapsed();
-        self.log_action(format!("\t{:.3} seconds to update {} cells", elapsed.as_secs_f32(), counter));
+    // numbers worth chording, followed by the cells left to click one by one
+    fn get_cells_to_reveal(&mut self) -> Result<Vec<Move>> {
+        let mut to_chord = vec![];
+        let mut chorded = BitSet::new(self.grid.len());
+        let mut to_reveal = vec![];
+        let mut to_discard = BitSet::new(self.grid.len());
+        for index in self.workset.iter() {
+            let (exhausted, neighbors) = self.grid.get_neighbors_to_reveal(index)?;
+            if exhausted {
+                to_discard.insert(index);
+
+                // a chord only works when the site shows our flags, and only saves clicks on two or more cells
+                let uncovered = neighbors.iter().filter(|&&(neighbor, _)| !chorded.contains(neighbor)).count();
+                if self.mark_flags && uncovered >= 2 {
+                    let posn = self.grid.posn(index);
+                    to_chord.push(Move::new(Action::Chord, posn, Rule::Count, vec![posn]));
+                    for (neighbor, _) in neighbors {
+                        chorded.insert(neighbor);
+                    }
+                    continue;
+                }
+            }
+            to_reveal.extend(neighbors.into_iter().map(|settled| self.settled_move(Action::Reveal, index, settled)));
+        }
+        self.workset.difference_with(&to_discard);
 
-        Ok(())
+        let mut seen = chorded;
+        to_reveal.retain(|found: &Move| seen.insert(self.grid.index(found.posn)));
+        to_chord.extend(to_reveal);
+        Ok(to_chord)
     }
+}
 
-    async fn reveal_all(&mut self, to_reveal: HashSet<CellWrapper>) {
-        if to_reveal.len() == self.blank.len() {
-            for cell in &to_reveal {
-                cell.0.borrow().click().await.unwrap();
-                self.blank.remove(cell);
-                self.numbers.insert(cell.clone());
-            }
-            self.log_action("complete".to_string());
-        } else {
-            let mut to_update = HashSet::new();
-            for cell in &to_reveal {
-                cell.0.borrow().click().await.unwrap();
-                to_update.insert(cell.clone());
+// the blank cells some number touches, the others only the mine count says anything about
+fn constrained_cells(frontier: &Frontier) -> HashSet<Posn> {
+    frontier.components().iter().flat_map(|component| component.cells.iter().copied()).collect()
+}
+
+impl Board<Position> {
+    // a board showing the given position, only flags can be placed on it
+    pub async fn from_position(logger: Logger, position: Position) -> Result<Self> {
+        let mut board = Board::new(logger, true, position);
+        board.update_board().await?;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{FirstClick, Game};
+    use crate::recording;
+
+    #[tokio::test]
+    async fn test_play_offline_game() {
+        for difficulty in [Difficulty::Beginner, Difficulty::Intermediate, Difficulty::Custom { rows: 24, cols: 20, mines: 60 }] {
+            let mut board = Board::new(Logger::quiet(), true, Game::from_difficulty(difficulty, 3));
+            assert_eq!((board.rows, board.cols), (difficulty.rows(), difficulty.cols()));
+            while !board.play().await.unwrap().won() {
+                board.reset_game().await.unwrap();
             }
-            self.update_from(to_update).await;
+            assert_eq!(board.backend.status(), Status::Won);
         }
     }
 
-    fn get_cells_to_flag(&self) -> HashSet<CellWrapper> { 
-        let mut to_flag = HashSet::new();
-        for cell in &self.workset {
-            let neighbors_to_flag = cell.0.borrow().get_neighbors_to_flag();
-            to_flag.extend(neighbors_to_flag.iter().cloned());
-        }
-        to_flag
+    #[tokio::test]
+    async fn test_update_board_applies_flood() {
+        // a single mine in the corner, opening the opposite corner floods everything else
+        let game = Game::with_mines(4, 4, &[Posn::new(0, 0)]);
+        let mut board = Board::new(Logger::quiet(), true, game);
+        board.backend.reveal(Posn::new(3, 3));
+        board.update_board().await.unwrap();
+
+        assert_eq!(board.grid.numbers().len(), 15);
+        // the game is won, so the engine flags the last mine and the board takes it as one,
+        // which leaves no number next to a blank cell
+        assert!(board.grid.blank().is_empty());
+        assert_eq!(board.grid.bombs().iter().collect::<Vec<_>>(), vec![0]);
+        assert!(board.workset.is_empty());
     }
-    
-    fn get_cells_to_reveal(&mut self) -> HashSet<CellWrapper> {
-        let mut to_reveal = HashSet::new();
-        let mut to_discard = HashSet::new();
-        for cell in &self.workset {
-            let (exhausted, neighbors) = cell.0.borrow().get_neighbors_to_reveal();
-            if exhausted {
-                to_discard.insert(cell.clone());
+
+    #[tokio::test]
+    async fn test_chord_satisfied_number() {
+        let game = Game::with_mines(3, 3, &[Posn::new(0, 0)]);
+        let mut board = Board::new(Logger::quiet(), true, game);
+        board.backend.reveal(Posn::new(1, 1));
+        board.update_board().await.unwrap();
+        let flag = Move::new(Action::Flag, Posn::new(0, 0), Rule::Enumeration { solutions: 1 }, vec![]);
+        board.flag_all(vec![flag]).await.unwrap();
+
+        let to_reveal = board.get_cells_to_reveal().unwrap();
+        assert_eq!(to_reveal, vec![Move::new(Action::Chord, Posn::new(1, 1), Rule::Count, vec![Posn::new(1, 1)])]);
+
+        board.reveal_all(to_reveal).await.unwrap();
+        assert_eq!(board.backend.status(), Status::Won);
+        assert_eq!(board.clicks, 2);
+    }
+
+    // compiles only if a board over any backend can move to a worker thread
+    fn spawn_game<B: Backend + 'static>(mut board: Board<B>) -> tokio::task::JoinHandle<Result<GameReport>> {
+        tokio::spawn(async move { board.play().await })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_play_on_worker_thread() {
+        let board = Board::new(Logger::quiet(), true, Game::from_difficulty(Difficulty::Beginner, 1));
+        let report = spawn_game(board).await.unwrap().unwrap();
+        assert_ne!(report.status, Status::Playing);
+    }
+
+    #[tokio::test]
+    async fn test_recording_replays_the_game() {
+        for seed in 0..5 {
+            let mut board = Board::new(Logger::quiet(), true, Game::from_difficulty(Difficulty::Intermediate, seed));
+            let report = board.play().await.unwrap();
+            let recording = Recording::from_json(&board.recording().to_json()).unwrap();
+            assert_eq!(recording.status, report.status);
+            assert_eq!(recording.mine_layout, Some(board.backend.mine_layout()));
+
+            let mut game = recording.game().unwrap();
+            for played in &recording.moves {
+                recording::apply(&mut game, &played.found);
             }
-            to_reveal.extend(neighbors.iter().cloned());
+            assert_eq!(game.status(), report.status);
         }
-        self.workset = self.workset.difference(&to_discard).cloned().collect();
-        to_reveal
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_opening_is_the_first_click() {
+        let game = Game::from_difficulty(Difficulty::Expert, 3).with_first_click(FirstClick::Zero);
+        let mut board = Board::new(Logger::quiet(), true, game);
+        board.set_opening(Opening::Center);
+        board.play().await.unwrap();
+
+        let first = &board.recording().moves[0].found;
+        assert_eq!((first.posn, first.rule), (Posn::new(8, 15), Rule::Opening { opening: Opening::Center }));
+        assert_eq!(board.recording().opening, "center");
+        assert_eq!(Game::square(&board.backend, Posn::new(8, 15)), Square::Open(0));
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_same_moves() {
+        let mut board = Board::new(Logger::quiet(), true, Game::from_difficulty(Difficulty::Expert, 11));
+        board.set_seed(11);
+        // the second game draws its own seed, replaying it must not need the first one
+        board.play().await.unwrap();
+        board.reset_game().await.unwrap();
+        board.play().await.unwrap();
+        let recording = board.recording().clone();
+        assert_ne!(recording.seed, Some(11));
+
+        let mut rerun = Board::new(Logger::quiet(), recording.flags, recording.game().unwrap());
+        rerun.set_seed(recording.seed.unwrap());
+        rerun.play().await.unwrap();
+        let moves = |recording: &Recording| recording.moves.iter().map(|played| played.found.clone()).collect::<Vec<_>>();
+        assert_eq!(moves(rerun.recording()), moves(&recording));
+        assert!(recording.moves.iter().any(|played| played.found.is_guess()));
+    }
+
+    #[tokio::test]
+    async fn test_load_position() {
+        let position: Position = "size 3x4\nmines 2\n__f_\n1221\n0000\n".parse().unwrap();
+        let mut board = Board::from_position(Logger::quiet(), position.clone()).await.unwrap();
+
+        assert_eq!(board.grid.bombs().len(), 1);
+        assert_eq!(board.grid.blank().len(), 3);
+        assert_eq!(board.position(), position);
+
+        // the 1 in the corner sees the flag, the cell next to it is safe
+        let to_reveal = board.get_cells_to_reveal().unwrap();
+        assert!(to_reveal.contains(&Move::new(Action::Reveal, Posn::new(0, 3), Rule::Count, vec![Posn::new(1, 3)])));
+    }
+
+    #[tokio::test]
+    async fn test_load_shown_mine() {
+        let position: Position = "size 3x4\nmines 2\n__*_\n1221\n0000\n".parse().unwrap();
+        let mut board = Board::from_position(Logger::quiet(), position.clone()).await.unwrap();
+
+        assert_eq!(board.grid.bombs().iter().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(board.position(), position);
+        assert_eq!(board.position().to_string(), "size 3x4\nmines 2\n__*_\n1221\n0000\n");
+
+        // the shown mine is known, so it is never flagged again
+        let analysis = board.analyze().unwrap();
+        assert_eq!(analysis.mines(), vec![Posn::new(0, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_explains_moves() {
+        let position: Position = "size 3x4\nmines 2\n__f_\n1221\n0000\n".parse().unwrap();
+        let mut board = Board::from_position(Logger::quiet(), position).await.unwrap();
+        let analysis = board.analyze().unwrap();
+
+        assert_eq!(analysis.mines(), vec![Posn::new(0, 1)]);
+        assert_eq!(analysis.safe(), vec![Posn::new(0, 0), Posn::new(0, 3)]);
+        // (0, 1) takes the subset rule between the 2s, (0, 0) only falls out of row reduction
+        let rules: Vec<(Posn, &str)> = analysis.moves.iter().map(|found| (found.posn, found.rule.name())).collect();
+        assert!(rules.contains(&(Posn::new(0, 0), "linear")));
+        assert!(rules.contains(&(Posn::new(0, 3), "count")));
+        assert!(analysis.guess().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mine_count_clears_the_interior() {
+        // the only mine is flagged, so even cells no number touches are safe
+        let position: Position = "size 4x4\nmines 1\nf1__\n11__\n____\n____\n".parse().unwrap();
+        let mut board = Board::from_position(Logger::quiet(), position).await.unwrap();
+        let analysis = board.analyze().unwrap();
+
+        assert_eq!(analysis.safe().len(), 12);
+        let rules: Vec<(Posn, &str)> = analysis.moves.iter().map(|found| (found.posn, found.rule.name())).collect();
+        assert!(rules.contains(&(Posn::new(3, 3), "mine_count")));
+        assert!(rules.contains(&(Posn::new(0, 2), "count")));
+    }
+}
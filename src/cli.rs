@@ -0,0 +1,246 @@
+use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crate::backend::webdriver::SITE;
+use crate::difficulty::Difficulty;
+use crate::logging::Format;
+use crate::session::{SessionOptions, WindowSize};
+use crate::solver::guess::{GuessPolicy, Informed, MinProbability};
+use crate::solver::opening::Opening;
+
+#[derive(Debug, Parser)]
+#[command(name = "minesweeper-bot", version, about = "Plays minesweeper on minesweeperonline.com or offline")]
+pub struct Cli {
+    /// Log every move, twice to add timings
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// How log lines are written to stderr
+    #[arg(long, value_enum, default_value_t = Format::Human, global = true)]
+    pub log_format: Format,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play until a game is won
+    Play(GameArgs),
+    /// Play a number of games in a row and report how many were won
+    Bench(BenchArgs),
+    /// Print the certain moves of a position, or the best guess when there are none
+    Solve(SolveArgs),
+    /// Play a recorded game again on the offline engine
+    Replay(ReplayArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BackendKind {
+    /// minesweeperonline.com through WebDriver
+    Web,
+    /// built-in engine, no browser needed
+    Offline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PolicyKind {
+    Informed,
+    MinProbability,
+}
+
+impl PolicyKind {
+    pub fn build(&self) -> Box<dyn GuessPolicy + Send> {
+        match self {
+            PolicyKind::Informed => Box::new(Informed),
+            PolicyKind::MinProbability => Box::new(MinProbability),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct GameArgs {
+    /// Where the game is played
+    #[arg(short, long, value_enum, default_value_t = BackendKind::Web)]
+    pub backend: BackendKind,
+
+    /// WebDriver endpoint, e.g. a local chromedriver
+    #[arg(long, default_value = "http://localhost:9515")]
+    pub webdriver: String,
+
+    /// Page the game is played on, e.g. a local copy of the site
+    #[arg(long, default_value = SITE)]
+    pub site: String,
+
+    /// Run the browser without a window
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Browser window size as WIDTHxHEIGHT
+    #[arg(long)]
+    pub window_size: Option<WindowSize>,
+
+    /// Extra command line argument for the browser, can be repeated
+    #[arg(long = "browser-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub browser_args: Vec<String>,
+
+    /// beginner, intermediate, expert or ROWSxCOLSxMINES
+    #[arg(short, long, default_value_t = Difficulty::Expert)]
+    pub difficulty: Difficulty,
+
+    /// Seed for the guesses and the offline mine layouts, random when omitted
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Do not mark deduced mines with flags on the board
+    #[arg(long)]
+    pub no_flags: bool,
+
+    /// How to pick a cell when nothing is certain
+    #[arg(short, long, value_enum, default_value_t = PolicyKind::Informed)]
+    pub policy: PolicyKind,
+
+    /// Where the first click of every game goes
+    #[arg(short, long, value_enum, default_value_t = Opening::Auto)]
+    pub opening: Opening,
+
+    /// Offline games keep the neighbors of the first click free as well, so it always opens an area
+    #[arg(long)]
+    pub zero_start: bool,
+
+    /// Save every game as a JSON recording in this directory
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+}
+
+impl GameArgs {
+    pub fn session(&self) -> SessionOptions {
+        SessionOptions {
+            webdriver: self.webdriver.clone(),
+            site: self.site.clone(),
+            headless: self.headless,
+            window_size: self.window_size,
+            browser_args: self.browser_args.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+
+    /// Number of games to play
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub games: usize,
+
+    /// Play the games once for every opening on the offline engine and compare their win rates
+    #[arg(long, conflicts_with_all = ["opening", "record"])]
+    pub compare_openings: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SolveArgs {
+    /// Position in the text notation, read from stdin when omitted or "-"
+    pub file: Option<PathBuf>,
+
+    /// How to pick a cell when nothing is certain
+    #[arg(short, long, value_enum, default_value_t = PolicyKind::Informed)]
+    pub policy: PolicyKind,
+
+    /// Print the moves and their reasons as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ReplayArgs {
+    /// Recording written by --record
+    pub file: PathBuf,
+
+    /// Stop after this many moves, e.g. right before the one that lost
+    #[arg(short = 'n', long)]
+    pub moves: Option<usize>,
+
+    /// Let the solver play the game again from the recorded seed and check it makes the same moves
+    #[arg(long, conflicts_with = "moves")]
+    pub rerun: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bench() {
+        let cli = Cli::parse_from(["minesweeper-bot", "-v", "bench", "-b", "offline", "-d", "beginner", "-n", "5", "--seed", "7"]);
+        assert_eq!(cli.verbose, 1);
+        assert_eq!(cli.log_format, Format::Human);
+        let Command::Bench(args) = cli.command else {
+            panic!("expected bench");
+        };
+        assert_eq!(args.games, 5);
+        assert_eq!(args.game.backend, BackendKind::Offline);
+        assert_eq!(args.game.difficulty, Difficulty::Beginner);
+        assert_eq!(args.game.seed, Some(7));
+        assert!(!args.game.no_flags);
+        assert!(!args.compare_openings);
+        assert!(Cli::try_parse_from(["minesweeper-bot", "bench", "--compare-openings", "-o", "corner"]).is_err());
+    }
+
+    #[test]
+    fn test_play_defaults() {
+        let cli = Cli::parse_from(["minesweeper-bot", "play"]);
+        let Command::Play(args) = cli.command else {
+            panic!("expected play");
+        };
+        assert_eq!(args.backend, BackendKind::Web);
+        assert_eq!(args.webdriver, "http://localhost:9515");
+        assert_eq!(args.difficulty, Difficulty::Expert);
+        assert_eq!(args.policy, PolicyKind::Informed);
+        assert_eq!(args.opening, Opening::Auto);
+        assert!(!args.zero_start);
+        assert_eq!(args.session().window_size, None);
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let cli = Cli::parse_from([
+            "minesweeper-bot", "play", "--headless", "--window-size", "1280x800", "--browser-arg", "--no-sandbox",
+        ]);
+        let Command::Play(args) = cli.command else {
+            panic!("expected play");
+        };
+        let session = args.session();
+        assert!(session.headless);
+        assert_eq!(session.window_size, Some(WindowSize { width: 1280, height: 800 }));
+        assert_eq!(session.browser_args, vec!["--no-sandbox".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_solve() {
+        let cli = Cli::parse_from(["minesweeper-bot", "solve", "stuck.txt"]);
+        let Command::Solve(args) = cli.command else {
+            panic!("expected solve");
+        };
+        assert_eq!(args.file, Some(PathBuf::from("stuck.txt")));
+        assert!(matches!(Cli::parse_from(["minesweeper-bot", "solve"]).command, Command::Solve(SolveArgs { file: None, .. })));
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        let cli = Cli::parse_from(["minesweeper-bot", "replay", "game.json", "-n", "12"]);
+        let Command::Replay(args) = cli.command else {
+            panic!("expected replay");
+        };
+        assert_eq!(args.file, PathBuf::from("game.json"));
+        assert_eq!(args.moves, Some(12));
+        assert!(!args.rerun);
+        assert!(Cli::try_parse_from(["minesweeper-bot", "replay", "game.json", "-n", "12", "--rerun"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_is_consistent() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
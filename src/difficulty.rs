@@ -0,0 +1,137 @@
+use std::fmt;
+use std::str::FromStr;
+
+// board dimensions and mine count of a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+    Custom { rows: usize, cols: usize, mines: usize },
+}
+
+impl Difficulty {
+    pub fn custom(rows: usize, cols: usize, mines: usize) -> Result<Self, String> {
+        if rows == 0 || cols == 0 {
+            return Err(format!("board must have at least one square, got {}x{}", rows, cols));
+        }
+        if mines >= rows * cols {
+            return Err(format!("{} mines do not fit on a {}x{} board", mines, rows, cols));
+        }
+        Ok(Difficulty::Custom { rows, cols, mines })
+    }
+
+    // the difficulties the site offers, in order
+    pub fn standard() -> [Difficulty; 3] {
+        [Difficulty::Beginner, Difficulty::Intermediate, Difficulty::Expert]
+    }
+
+    // the standard difficulty with these dimensions, a custom one otherwise
+    pub fn of(rows: usize, cols: usize, mines: usize) -> Self {
+        Difficulty::standard()
+            .into_iter()
+            .find(|standard| (standard.rows(), standard.cols(), standard.mines()) == (rows, cols, mines))
+            .unwrap_or(Difficulty::Custom { rows, cols, mines })
+    }
+
+    // share of the squares holding a mine
+    pub fn density(&self) -> f64 {
+        self.mines() as f64 / (self.rows() * self.cols()) as f64
+    }
+
+    pub fn rows(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 9,
+            Difficulty::Intermediate => 16,
+            Difficulty::Expert => 16,
+            Difficulty::Custom { rows, .. } => *rows,
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 9,
+            Difficulty::Intermediate => 16,
+            Difficulty::Expert => 30,
+            Difficulty::Custom { cols, .. } => *cols,
+        }
+    }
+
+    pub fn mines(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 10,
+            Difficulty::Intermediate => 40,
+            Difficulty::Expert => 99,
+            Difficulty::Custom { mines, .. } => *mines,
+        }
+    }
+}
+
+// "beginner", "intermediate", "expert" or a custom "ROWSxCOLSxMINES"
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "beginner" => Ok(Difficulty::Beginner),
+            "intermediate" => Ok(Difficulty::Intermediate),
+            "expert" => Ok(Difficulty::Expert),
+            custom => {
+                let parts: Vec<usize> = custom
+                    .split('x')
+                    .map(|part| part.trim().parse::<usize>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("unknown difficulty: {}", s))?;
+                match parts[..] {
+                    [rows, cols, mines] => Difficulty::custom(rows, cols, mines),
+                    _ => Err(format!("custom difficulty should look like ROWSxCOLSxMINES, got {}", s)),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Beginner => write!(f, "beginner"),
+            Difficulty::Intermediate => write!(f, "intermediate"),
+            Difficulty::Expert => write!(f, "expert"),
+            Difficulty::Custom { rows, cols, mines } => write!(f, "{}x{}x{}", rows, cols, mines),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_sizes() {
+        assert_eq!((Difficulty::Beginner.rows(), Difficulty::Beginner.cols(), Difficulty::Beginner.mines()), (9, 9, 10));
+        assert_eq!((Difficulty::Intermediate.rows(), Difficulty::Intermediate.cols(), Difficulty::Intermediate.mines()), (16, 16, 40));
+        assert_eq!((Difficulty::Expert.rows(), Difficulty::Expert.cols(), Difficulty::Expert.mines()), (16, 30, 99));
+    }
+
+    #[test]
+    fn test_of_finds_the_standard_sizes() {
+        assert_eq!(Difficulty::of(16, 30, 99), Difficulty::Expert);
+        assert_eq!(Difficulty::of(30, 16, 99), Difficulty::Custom { rows: 30, cols: 16, mines: 99 });
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("Expert".parse::<Difficulty>(), Ok(Difficulty::Expert));
+        assert_eq!("20x24x120".parse::<Difficulty>(), Ok(Difficulty::Custom { rows: 20, cols: 24, mines: 120 }));
+        assert!("3x3x9".parse::<Difficulty>().is_err());
+        assert!("9x9".parse::<Difficulty>().is_err());
+        assert!("hard".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for difficulty in [Difficulty::Beginner, Difficulty::Intermediate, Difficulty::Expert, Difficulty::Custom { rows: 5, cols: 7, mines: 3 }] {
+            assert_eq!(difficulty.to_string().parse::<Difficulty>(), Ok(difficulty));
+        }
+    }
+}
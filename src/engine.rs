@@ -0,0 +1,411 @@
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use std::collections::VecDeque;
+use crate::difficulty::Difficulty;
+use crate::posn::Posn;
+use crate::seed::{self, Stream};
+
+// what a player can see on a single square of an offline game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Square {
+    Hidden,
+    Flagged,
+    Open(u8),
+    // a mine uncovered once the game is lost
+    Mine,
+    // the mine that was clicked
+    Exploded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Playing => "playing",
+            Status::Won => "won",
+            Status::Lost => "lost",
+        }
+    }
+}
+
+// what a game promises about its first click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FirstClick {
+    // the mines are there before the click, e.g. a recorded layout
+    Unprotected,
+    // the clicked square never holds a mine, like on minesweeperonline.com
+    Safe,
+    // neither the clicked square nor its neighbors hold a mine, so the click always opens an area
+    Zero,
+}
+
+impl FirstClick {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirstClick::Unprotected => "unprotected",
+            FirstClick::Safe => "safe",
+            FirstClick::Zero => "zero",
+        }
+    }
+}
+
+// pure-Rust minesweeper game, mirrors the rules of minesweeperonline.com
+#[derive(Debug, Clone)]
+pub struct Game {
+    rows: usize,
+    cols: usize,
+    mines: usize,
+    layout: Vec<bool>,
+    squares: Vec<Square>,
+    // mines are placed on the first reveal unless a layout was given
+    placed: bool,
+    fixed_layout: bool,
+    first_click: FirstClick,
+    opened: usize,
+    status: Status,
+    rng: StdRng,
+}
+
+impl Game {
+    // new game whose layout is generated from the seed on the first reveal
+    pub fn new(rows: usize, cols: usize, mines: usize, seed: u64) -> Self {
+        assert!(rows > 0 && cols > 0, "board must have at least one square");
+        assert!(mines < rows * cols, "at least one square must be free of mines");
+
+        Game {
+            rows,
+            cols,
+            mines,
+            layout: vec![false; rows * cols],
+            squares: vec![Square::Hidden; rows * cols],
+            placed: false,
+            fixed_layout: false,
+            first_click: FirstClick::Safe,
+            opened: 0,
+            status: Status::Playing,
+            rng: seed::rng(seed, Stream::Layouts),
+        }
+    }
+
+    pub fn from_difficulty(difficulty: Difficulty, seed: u64) -> Self {
+        Game::new(difficulty.rows(), difficulty.cols(), difficulty.mines(), seed)
+    }
+
+    // new game with a known mine layout, the first click is not protected
+    pub fn with_mines(rows: usize, cols: usize, mines: &[Posn]) -> Self {
+        let mut game = Game::new(rows, cols, 0, 0);
+        for posn in mines {
+            assert!(posn.in_range(rows as i32, cols as i32), "mine out of range: {}", posn);
+            let index = game.index(*posn);
+            if !game.layout[index] {
+                game.layout[index] = true;
+                game.mines += 1;
+            }
+        }
+        assert!(game.mines < rows * cols, "at least one square must be free of mines");
+        game.placed = true;
+        game.fixed_layout = true;
+        game.first_click = FirstClick::Unprotected;
+        game
+    }
+
+    // what the first click of a generated layout is protected from, a given layout ignores it
+    pub fn with_first_click(mut self, first_click: FirstClick) -> Self {
+        if !self.fixed_layout {
+            self.first_click = first_click;
+        }
+        self
+    }
+
+    pub fn first_click(&self) -> FirstClick {
+        self.first_click
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn square(&self, posn: Posn) -> Square {
+        self.squares[self.index(posn)]
+    }
+
+    // positions of every mine, empty until the layout has been generated
+    pub fn mine_layout(&self) -> Vec<Posn> {
+        (0..self.layout.len())
+            .filter(|&index| self.layout[index])
+            .map(|index| self.posn(index))
+            .collect()
+    }
+
+    // opens a square, returns every position that was opened by this click
+    pub fn reveal(&mut self, posn: Posn) -> Vec<Posn> {
+        let index = self.index(posn);
+        if self.status != Status::Playing || self.squares[index] != Square::Hidden {
+            return vec![];
+        }
+
+        if !self.placed {
+            self.place_mines(index);
+        }
+
+        if self.layout[index] {
+            self.lose(index);
+            return vec![posn];
+        }
+
+        let opened = self.flood(index);
+        if self.opened == self.rows * self.cols - self.mines {
+            self.win();
+        }
+        opened
+    }
+
+    // places a flag on a hidden square, returns whether the square changed
+    pub fn flag(&mut self, posn: Posn) -> bool {
+        let index = self.index(posn);
+        if self.status != Status::Playing || self.squares[index] != Square::Hidden {
+            return false;
+        }
+        self.squares[index] = Square::Flagged;
+        true
+    }
+
+    // opens the hidden neighbors of a number whose flags match its value
+    pub fn chord(&mut self, posn: Posn) -> Vec<Posn> {
+        let index = self.index(posn);
+        let Square::Open(count) = self.squares[index] else {
+            return vec![];
+        };
+        let neighbors = self.neighbors(index);
+        let flags = neighbors.iter().filter(|&&n| self.squares[n] == Square::Flagged).count();
+        if self.status != Status::Playing || flags != count as usize {
+            return vec![];
+        }
+
+        let mut opened = vec![];
+        for neighbor in neighbors {
+            let posn = self.posn(neighbor);
+            opened.extend(self.reveal(posn));
+        }
+        opened
+    }
+
+    // starts over, a generated layout is rolled again from the same rng
+    pub fn reset(&mut self) {
+        if !self.fixed_layout {
+            self.layout = vec![false; self.rows * self.cols];
+            self.placed = false;
+        }
+        self.squares = vec![Square::Hidden; self.rows * self.cols];
+        self.opened = 0;
+        self.status = Status::Playing;
+    }
+
+    fn index(&self, posn: Posn) -> usize {
+        posn.row as usize * self.cols + posn.col as usize
+    }
+
+    fn posn(&self, index: usize) -> Posn {
+        Posn::new((index / self.cols) as i32, (index % self.cols) as i32)
+    }
+
+    fn neighbors(&self, index: usize) -> Vec<usize> {
+        self.posn(index)
+            .surrounding_in_range(self.rows as i32, self.cols as i32)
+            .into_iter()
+            .map(|posn| self.index(posn))
+            .collect()
+    }
+
+    fn adjacent_mines(&self, index: usize) -> u8 {
+        self.neighbors(index).into_iter().filter(|&n| self.layout[n]).count() as u8
+    }
+
+    // first click safety: the clicked square never holds a mine, nor its neighbors for a zero start
+    fn place_mines(&mut self, safe: usize) {
+        let mut free = self.neighbors(safe);
+        free.push(safe);
+        if self.first_click == FirstClick::Zero && self.mines <= self.rows * self.cols - free.len() {
+            let candidates: Vec<usize> = (0..self.rows * self.cols).filter(|index| !free.contains(index)).collect();
+            for pick in sample(&mut self.rng, candidates.len(), self.mines) {
+                self.layout[candidates[pick]] = true;
+            }
+        } else {
+            let candidates = self.rows * self.cols - 1;
+            for pick in sample(&mut self.rng, candidates, self.mines) {
+                let index = if pick >= safe { pick + 1 } else { pick };
+                self.layout[index] = true;
+            }
+        }
+        self.placed = true;
+    }
+
+    // opens the square and spreads through zeros, bfs approach
+    fn flood(&mut self, start: usize) -> Vec<Posn> {
+        let mut opened = vec![];
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(index) = queue.pop_front() {
+            if self.squares[index] != Square::Hidden {
+                continue;
+            }
+            let count = self.adjacent_mines(index);
+            self.squares[index] = Square::Open(count);
+            self.opened += 1;
+            opened.push(self.posn(index));
+
+            if count == 0 {
+                queue.extend(self.neighbors(index));
+            }
+        }
+        opened
+    }
+
+    fn lose(&mut self, exploded: usize) {
+        for index in 0..self.layout.len() {
+            if self.layout[index] && self.squares[index] == Square::Hidden {
+                self.squares[index] = Square::Mine;
+            }
+        }
+        self.squares[exploded] = Square::Exploded;
+        self.status = Status::Lost;
+    }
+
+    // like the site, every remaining mine is flagged on a win
+    fn win(&mut self) {
+        for index in 0..self.layout.len() {
+            if self.layout[index] {
+                self.squares[index] = Square::Flagged;
+            }
+        }
+        self.status = Status::Won;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_click_is_safe() {
+        for seed in 0..50 {
+            let mut game = Game::new(9, 9, 80, seed);
+            let opened = game.reveal(Posn::new(4, 4));
+            assert_eq!(opened, vec![Posn::new(4, 4)]);
+            assert_eq!(game.square(Posn::new(4, 4)), Square::Open(8));
+            assert_eq!(game.mine_layout().len(), 80);
+            assert_eq!(game.status(), Status::Won);
+        }
+    }
+
+    #[test]
+    fn test_zero_start_opens_an_area() {
+        for seed in 0..50 {
+            let mut game = Game::new(16, 30, 99, seed).with_first_click(FirstClick::Zero);
+            assert!(game.reveal(Posn::new(0, 0)).len() >= 4);
+            assert_eq!(game.square(Posn::new(0, 0)), Square::Open(0));
+        }
+        // too many mines to keep the neighbors free, the click is still safe
+        let mut game = Game::new(3, 3, 8, 1).with_first_click(FirstClick::Zero);
+        assert_eq!(game.reveal(Posn::new(1, 1)), vec![Posn::new(1, 1)]);
+    }
+
+    #[test]
+    fn test_same_seed_same_layout() {
+        let mut first = Game::new(16, 30, 99, 7);
+        let mut second = Game::new(16, 30, 99, 7);
+        first.reveal(Posn::new(0, 0));
+        second.reveal(Posn::new(0, 0));
+        assert_eq!(first.mine_layout(), second.mine_layout());
+    }
+
+    #[test]
+    fn test_flood_opens_zero_region() {
+        let mut game = Game::with_mines(3, 4, &[Posn::new(0, 3)]);
+        let opened = game.reveal(Posn::new(2, 0));
+        assert_eq!(opened.len(), 11);
+        assert_eq!(game.square(Posn::new(0, 2)), Square::Open(1));
+        assert_eq!(game.square(Posn::new(1, 3)), Square::Open(1));
+        assert_eq!(game.square(Posn::new(0, 3)), Square::Flagged);
+        assert_eq!(game.status(), Status::Won);
+    }
+
+    #[test]
+    fn test_flood_stops_at_flags() {
+        let mut game = Game::with_mines(3, 3, &[Posn::new(2, 2)]);
+        assert!(game.flag(Posn::new(0, 1)));
+        let opened = game.reveal(Posn::new(0, 0));
+        assert!(!opened.contains(&Posn::new(0, 1)));
+        assert_eq!(game.square(Posn::new(0, 1)), Square::Flagged);
+        assert_eq!(game.status(), Status::Playing);
+    }
+
+    #[test]
+    fn test_reveal_mine_loses() {
+        let mut game = Game::with_mines(2, 2, &[Posn::new(0, 0), Posn::new(1, 1)]);
+        game.reveal(Posn::new(0, 0));
+        assert_eq!(game.status(), Status::Lost);
+        assert_eq!(game.square(Posn::new(0, 0)), Square::Exploded);
+        assert_eq!(game.square(Posn::new(1, 1)), Square::Mine);
+        assert!(game.reveal(Posn::new(0, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_flagged_square_cannot_be_revealed() {
+        let mut game = Game::new(5, 5, 5, 1);
+        assert!(game.flag(Posn::new(1, 1)));
+        assert!(!game.flag(Posn::new(1, 1)));
+        assert!(game.reveal(Posn::new(1, 1)).is_empty());
+        assert_eq!(game.square(Posn::new(1, 1)), Square::Flagged);
+    }
+
+    #[test]
+    fn test_chord_opens_unflagged_neighbors() {
+        let mut game = Game::with_mines(3, 3, &[Posn::new(0, 0)]);
+        game.reveal(Posn::new(1, 1));
+        assert!(game.chord(Posn::new(1, 1)).is_empty());
+        game.flag(Posn::new(0, 0));
+        let opened = game.chord(Posn::new(1, 1));
+        assert_eq!(opened.len(), 7);
+        assert_eq!(game.status(), Status::Won);
+    }
+
+    #[test]
+    fn test_chord_with_wrong_flag_loses() {
+        let mut game = Game::with_mines(3, 3, &[Posn::new(0, 0)]);
+        game.reveal(Posn::new(1, 1));
+        game.flag(Posn::new(2, 2));
+        game.chord(Posn::new(1, 1));
+        assert_eq!(game.status(), Status::Lost);
+        assert_eq!(game.square(Posn::new(0, 0)), Square::Exploded);
+    }
+
+    #[test]
+    fn test_reset_keeps_fixed_layout() {
+        let mines = [Posn::new(0, 0)];
+        let mut game = Game::with_mines(3, 3, &mines);
+        game.reveal(Posn::new(0, 0));
+        game.reset();
+        assert_eq!(game.status(), Status::Playing);
+        assert_eq!(game.square(Posn::new(0, 0)), Square::Hidden);
+        assert_eq!(game.mine_layout(), mines.to_vec());
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+use fantoccini::error::{CmdError, NewSessionError};
+use crate::backend::Status;
+
+#[derive(Debug)]
+pub enum Error {
+    // a WebDriver command failed, e.g. a stale element or a dropped connection
+    WebDriver(CmdError),
+    // no WebDriver session could be started
+    Session(NewSessionError),
+    // the page does not look like a minesweeper board
+    UnexpectedDom(String),
+    // the numbers on the board cannot all be satisfied
+    Contradiction(String),
+    // the bot's own bookkeeping went wrong, e.g. flagging an opened cell
+    InvalidState(String),
+    // the game ended while a move was being played
+    GameOver(Status),
+    // a text position could not be read
+    Notation(String),
+    // a game recording could not be read or did not replay the same way
+    Recording(String),
+    // a file or stdin could not be read
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    // the browser or the connection to it failed, a new session may well work
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Error::WebDriver(_) | Error::Session(_))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WebDriver(error) => write!(f, "webdriver command failed: {}", error),
+            Error::Session(error) => write!(f, "failed to connect to webdriver: {}", error),
+            Error::UnexpectedDom(message) => write!(f, "unexpected page state: {}", message),
+            Error::Contradiction(message) => write!(f, "contradiction on the board: {}", message),
+            Error::InvalidState(message) => write!(f, "invalid board state: {}", message),
+            Error::GameOver(status) => write!(f, "game is over: {}", status.name()),
+            Error::Notation(message) => write!(f, "invalid position: {}", message),
+            Error::Recording(message) => write!(f, "invalid recording: {}", message),
+            Error::Io(error) => write!(f, "io error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::WebDriver(error) => Some(error),
+            Error::Session(error) => Some(error),
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CmdError> for Error {
+    fn from(error: CmdError) -> Self {
+        Error::WebDriver(error)
+    }
+}
+
+impl From<NewSessionError> for Error {
+    fn from(error: NewSessionError) -> Self {
+        Error::Session(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,228 @@
+use crate::backend::Square;
+use crate::bitset::BitSet;
+use crate::cell::Cell;
+use crate::error::{Error, Result};
+use crate::posn::Posn;
+
+// a cell settled by a number, with the neighboring number that helped if it took two
+pub type Settled = (usize, Option<usize>);
+
+// every cell of the board in row-major order, with the indices of its neighbors worked out once.
+// each cell is in exactly one of the blank, bomb and number sets, the rules all read them from here
+pub struct Grid {
+    cols: usize,
+    cells: Vec<Cell>,
+    neighbors: Vec<Vec<usize>>,
+    blank: BitSet,
+    bombs: BitSet,
+    numbers: BitSet,
+}
+
+impl Grid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let mut cells = Vec::with_capacity(rows * cols);
+        let mut neighbors = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                let posn = Posn::new(row as i32, col as i32);
+                let mut indices: Vec<usize> = posn
+                    .surrounding_in_range(rows as i32, cols as i32)
+                    .into_iter()
+                    .map(|neighbor| neighbor.row as usize * cols + neighbor.col as usize)
+                    .collect();
+                indices.sort_unstable();
+                cells.push(Cell::new(posn));
+                neighbors.push(indices);
+            }
+        }
+        let size = rows * cols;
+        Grid {
+            cols,
+            cells,
+            neighbors,
+            blank: BitSet::full(size),
+            bombs: BitSet::new(size),
+            numbers: BitSet::new(size),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn index(&self, posn: Posn) -> usize {
+        posn.row as usize * self.cols + posn.col as usize
+    }
+
+    pub fn posn(&self, index: usize) -> Posn {
+        self.cells[index].posn
+    }
+
+    pub fn cell(&self, index: usize) -> &Cell {
+        &self.cells[index]
+    }
+
+    pub fn cell_mut(&mut self, index: usize) -> &mut Cell {
+        &mut self.cells[index]
+    }
+
+    pub fn neighbors(&self, index: usize) -> &[usize] {
+        &self.neighbors[index]
+    }
+
+    pub fn blank(&self) -> &BitSet {
+        &self.blank
+    }
+
+    pub fn bombs(&self) -> &BitSet {
+        &self.bombs
+    }
+
+    pub fn numbers(&self) -> &BitSet {
+        &self.numbers
+    }
+
+    pub fn reset(&mut self) {
+        self.cells.iter_mut().for_each(Cell::reset);
+        self.blank = BitSet::full(self.cells.len());
+        self.bombs.clear();
+        self.numbers.clear();
+    }
+
+    pub fn flag(&mut self, index: usize) -> Result<()> {
+        if !self.blank.remove(index) {
+            return Err(Error::InvalidState(format!("cannot flag non-blank cell {}", self.posn(index).coords())));
+        }
+        self.bombs.insert(index);
+        Ok(())
+    }
+
+    // moves a blank cell whose square reads as a number to the numbers
+    pub fn mark_number(&mut self, index: usize) -> Result<()> {
+        let cell = &self.cells[index];
+        if !matches!(cell.square, Square::Open(_)) {
+            return Err(Error::UnexpectedDom(format!("{:?} at {} is not a number", cell.square, cell.posn.coords())));
+        }
+        if !self.blank.remove(index) {
+            return Err(Error::InvalidState(format!("cell {} is not blank", cell.posn.coords())));
+        }
+        self.numbers.insert(index);
+        Ok(())
+    }
+
+    fn neighbors_in(&self, index: usize, set: &BitSet) -> Vec<usize> {
+        self.neighbors(index).iter().copied().filter(|&neighbor| set.contains(neighbor)).collect()
+    }
+
+    fn is_non_zero_number(&self, index: usize) -> bool {
+        self.numbers.contains(index) && !matches!(self.cells[index].square, Square::Open(0))
+    }
+
+    pub fn bomb_neighbors(&self, index: usize) -> Vec<usize> {
+        self.neighbors_in(index, &self.bombs)
+    }
+
+    pub fn blank_neighbors(&self, index: usize) -> Vec<usize> {
+        self.neighbors_in(index, &self.blank)
+    }
+
+    pub fn non_zero_number_neighbors(&self, index: usize) -> Vec<usize> {
+        self.neighbors(index).iter().copied().filter(|&neighbor| self.is_non_zero_number(neighbor)).collect()
+    }
+
+    // a cell that is no number comes out short, which the frontier reports as a contradiction
+    pub fn bombs_remaining(&self, index: usize) -> i32 {
+        self.cells[index].get_number().unwrap_or(-1) - self.bomb_neighbors(index).len() as i32
+    }
+
+    pub fn get_more_to_flag(&self, index: usize) -> Vec<Settled> {
+        let mut pattern_flag = vec![];
+        let this_blank = self.blank_neighbors(index);
+
+        for neighbor in self.non_zero_number_neighbors(index) {
+            let diff: Vec<usize> = self.blank_neighbors(neighbor)
+                .into_iter()
+                .filter(|cell| !this_blank.contains(cell))
+                .collect();
+            if diff.len() as i32 == self.bombs_remaining(neighbor) - self.bombs_remaining(index) {
+                pattern_flag.extend(diff.into_iter().map(|cell| (cell, Some(neighbor))));
+            }
+        }
+        pattern_flag
+    }
+
+    pub fn get_more_to_reveal(&self, index: usize) -> Vec<Settled> {
+        let mut pattern_reveal = vec![];
+        let this_blank = self.blank_neighbors(index);
+
+        for neighbor in self.non_zero_number_neighbors(index) {
+            let other_blank = self.blank_neighbors(neighbor);
+
+            let is_subset = this_blank.iter().all(|cell| other_blank.contains(cell));
+            if is_subset && self.bombs_remaining(index) == self.bombs_remaining(neighbor) {
+                pattern_reveal.extend(
+                    other_blank
+                        .into_iter()
+                        .filter(|cell| !this_blank.contains(cell))
+                        .map(|cell| (cell, Some(neighbor)))
+                );
+            }
+        }
+        pattern_reveal
+    }
+
+    pub fn should_add_to_workset(&self, index: usize) -> bool {
+        self.is_non_zero_number(index) && !self.blank_neighbors(index).is_empty()
+    }
+
+    pub fn get_neighbors_to_flag(&self, index: usize) -> Vec<Settled> {
+        let blank_neighbors = self.blank_neighbors(index);
+        if blank_neighbors.len() as i32 == self.bombs_remaining(index) {
+            blank_neighbors.into_iter().map(|cell| (cell, None)).collect()
+        } else {
+            self.get_more_to_flag(index)
+        }
+    }
+
+    pub fn get_neighbors_to_reveal(&self, index: usize) -> Result<(bool, Vec<Settled>)> {
+        if !self.numbers.contains(index) {
+            return Err(Error::InvalidState(format!("cell {} is not a number", self.posn(index).coords())));
+        }
+        if self.cells[index].get_number()? == self.bomb_neighbors(index).len() as i32 {
+            Ok((true, self.blank_neighbors(index).into_iter().map(|cell| (cell, None)).collect()))
+        } else {
+            Ok((false, self.get_more_to_reveal(index)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 4);
+        assert_eq!(grid.len(), 12);
+        assert_eq!(grid.neighbors(0), &[1, 4, 5]);
+        assert_eq!(grid.neighbors(5), &[0, 1, 2, 4, 6, 8, 9, 10]);
+        assert_eq!(grid.index(Posn::new(2, 3)), 11);
+        assert_eq!(grid.posn(11), Posn::new(2, 3));
+    }
+
+    #[test]
+    fn test_one_two_one_patterns() {
+        // a 1-2-1 along the bottom of a 2x3 board, only the top row is blank
+        let mut grid = Grid::new(2, 3);
+        for (col, digit) in [1, 2, 1].into_iter().enumerate() {
+            grid.cell_mut(3 + col).square = Square::Open(digit);
+            grid.mark_number(3 + col).unwrap();
+        }
+        assert_eq!(grid.blank().len(), 3);
+        assert!(grid.flag(3).is_err());
+
+        assert_eq!(grid.get_neighbors_to_flag(3), vec![(2, Some(4))]);
+        assert_eq!(grid.get_neighbors_to_flag(5), vec![(0, Some(4))]);
+        assert_eq!(grid.get_neighbors_to_reveal(3).unwrap(), (false, vec![]));
+    }
+}
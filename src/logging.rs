@@ -0,0 +1,132 @@
+use std::fmt;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+
+// how much gets logged, every level includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    // the solver did something it should never do, e.g. lost on a deduction
+    Warn,
+    // the outcome of every game
+    Info,
+    // every move and its reason
+    Debug,
+    // timings of board reads and setup
+    Trace,
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One readable line per event
+    Human,
+    /// One JSON object per line, with every field of the event
+    Json,
+}
+
+// writes events to stderr, leaving stdout to the results of a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Logger {
+    // None logs nothing at all
+    level: Option<Level>,
+    format: Format,
+}
+
+impl Logger {
+    pub fn new(level: Level, format: Format) -> Self {
+        Logger { level: Some(level), format }
+    }
+
+    // -v shows every move, -vv the timings as well
+    pub fn from_verbosity(verbose: u8, format: Format) -> Self {
+        let level = match verbose {
+            0 => Level::Info,
+            1 => Level::Debug,
+            _ => Level::Trace,
+        };
+        Logger::new(level, format)
+    }
+
+    // logs nothing, for tests
+    #[cfg(test)]
+    pub fn quiet() -> Self {
+        Logger { level: None, format: Format::Human }
+    }
+
+    pub fn enabled(&self, level: Level) -> bool {
+        self.level.is_some_and(|max| level <= max)
+    }
+
+    // the fields are only built when the level is enabled
+    pub fn log(&self, level: Level, event: &str, message: impl fmt::Display, fields: impl FnOnce() -> Value) {
+        if !self.enabled(level) {
+            return;
+        }
+        let line = self.render(level, event, &message, fields());
+        // a closed stderr is no reason to stop playing
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+    }
+
+    fn render(&self, level: Level, event: &str, message: &dyn fmt::Display, fields: Value) -> String {
+        match self.format {
+            Format::Human => match level {
+                Level::Warn => format!("warning: {}", message),
+                Level::Info => message.to_string(),
+                Level::Debug | Level::Trace => format!("\t{}", message),
+            },
+            Format::Json => {
+                let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64());
+                let mut line = Map::new();
+                line.insert("time".to_string(), json!(time));
+                line.insert("level".to_string(), json!(level.name()));
+                line.insert("event".to_string(), json!(event));
+                line.insert("message".to_string(), json!(message.to_string()));
+                if let Value::Object(fields) = fields {
+                    line.extend(fields);
+                }
+                Value::Object(line).to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        let logger = Logger::from_verbosity(1, Format::Human);
+        assert!(logger.enabled(Level::Warn));
+        assert!(logger.enabled(Level::Debug));
+        assert!(!logger.enabled(Level::Trace));
+        assert!(!Logger::quiet().enabled(Level::Warn));
+    }
+
+    #[test]
+    fn test_render() {
+        let fields = json!({ "cells": 12, "seconds": 0.5 });
+        let human = Logger::new(Level::Trace, Format::Human);
+        assert_eq!(human.render(Level::Trace, "update", &"updated 12 cells", fields.clone()), "\tupdated 12 cells");
+
+        let line = Logger::new(Level::Trace, Format::Json).render(Level::Trace, "update", &"updated 12 cells", fields);
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "trace");
+        assert_eq!(value["event"], "update");
+        assert_eq!(value["cells"], 12);
+        assert_eq!(value["seconds"], 0.5);
+        assert!(value["time"].as_f64().unwrap() > 0.0);
+    }
+}
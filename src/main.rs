@@ -1,22 +1,473 @@
-use fantoccini::{ClientBuilder, Locator};
-use fantoccini::actions::{Actions, MouseActions, PointerAction, InputSource, MOUSE_BUTTON_RIGHT};
+use std::io::Read;
+use std::path::Path;
+use clap::{Parser, ValueEnum};
+use serde_json::json;
 use tokio::time::Duration;
-use tokio;
 mod info;
 mod posn;
 mod cell;
 mod board;
 use board::Board;
-mod cell_wrapper;
+mod grid;
+mod bitset;
+mod notation;
+use notation::Position;
+mod analysis;
+mod moves;
+mod recording;
+use recording::Recording;
+mod engine;
+use engine::Game;
+mod backend;
+mod solver;
+use solver::opening::Opening;
+use backend::{Backend, FirstClick, Status};
+use backend::webdriver::WebDriver;
+mod difficulty;
+mod cli;
+use cli::{BackendKind, Cli, Command, GameArgs, PolicyKind, ReplayArgs, SolveArgs};
+mod stats;
+use stats::{GameReport, LossCause, Summary};
+mod logging;
+use logging::{Level, Logger};
+mod session;
+mod seed;
+mod error;
+use error::{Error, Result};
+
+// how many times in a row a lost browser session is started again
+const MAX_RESTARTS: u32 = 5;
 
 #[tokio::main]
-async fn main() -> Result<(), fantoccini::error::CmdError> {
-    let client = ClientBuilder::native().connect("http://localhost:9515").await.expect("failed to connect to webdriver.");
+async fn main() {
+    if let Err(error) = start(Cli::parse()).await {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn start(cli: Cli) -> Result<()> {
+    let logger = Logger::from_verbosity(cli.verbose, cli.log_format);
+
+    let (args, games) = match cli.command {
+        Command::Play(args) => (args, None),
+        Command::Bench(args) if args.compare_openings => return compare_openings(&args.game, args.games, logger).await,
+        Command::Bench(args) => (args.game, Some(args.games)),
+        Command::Solve(args) => return solve(&args, logger).await,
+        Command::Replay(args) if args.rerun => return rerun(&args, logger).await,
+        Command::Replay(args) => return replay(&args),
+    };
+    if let Some(directory) = &args.record {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    let seed = choose_seed(&args, &logger);
+    match args.backend {
+        BackendKind::Offline => {
+            let game = offline_game(&args, seed);
+            run_and_close(new_board(&args, logger, game, seed), games, &args).await
+        }
+        BackendKind::Web => {
+            let backend = WebDriver::connect(args.session(), args.difficulty).await?;
+            run_and_close(new_board(&args, logger, backend, seed), games, &args).await
+        }
+    }
+}
+
+// one seed for the layouts and the guesses, each drawing their own stream from it, logged so any run can be repeated
+fn choose_seed(args: &GameArgs, logger: &Logger) -> u64 {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    logger.log(Level::Info, "seed", format_args!("seed {}", seed), || json!({ "seed": seed }));
+    seed
+}
+
+fn offline_game(args: &GameArgs, seed: u64) -> Game {
+    let first_click = if args.zero_start { FirstClick::Zero } else { FirstClick::Safe };
+    Game::from_difficulty(args.difficulty, seed).with_first_click(first_click)
+}
+
+// plays the games and closes the backend however they end, ctrl-c included
+async fn run_and_close<B: Backend>(mut board: Board<B>, games: Option<usize>, args: &GameArgs) -> Result<()> {
+    let result = tokio::select! {
+        result = run(&mut board, games, args) => result.map(|summary| {
+            if games.is_some() {
+                print!("{}", summary);
+            }
+        }),
+        _ = tokio::signal::ctrl_c() => {
+            board.logger.log(Level::Warn, "interrupted", "interrupted, closing the session", || json!({}));
+            Ok(())
+        }
+    };
+    let closed = board.close().await;
+    result.and(closed)
+}
+
+// analyzes a position from a file or stdin and prints the result
+async fn solve(args: &SolveArgs, logger: Logger) -> Result<()> {
+    let text = match &args.file {
+        Some(path) if path.as_os_str() != "-" => std::fs::read_to_string(path)?,
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+    let position: Position = text.parse()?;
+
+    let mut board = Board::from_position(logger, position).await?;
+    board.set_policy(args.policy.build());
+    let analysis = board.analyze()?;
+    if args.json {
+        println!("{}", analysis.to_json());
+    } else {
+        print!("{}", analysis);
+    }
+    Ok(())
+}
 
-    let mut board = Board::new(true, true, client).await?;
+// plays the moves of a recording on the offline engine and prints the position they lead to
+fn replay(args: &ReplayArgs) -> Result<()> {
+    let recording = Recording::load(&args.file)?;
+    let moves = args.moves.unwrap_or(recording.moves.len()).min(recording.moves.len());
+    let mut game = recording.game()?;
 
-    board.play().await;
+    for (number, played) in recording.moves[..moves].iter().enumerate() {
+        println!("{:>4} {:>9.3}s  {}", number + 1, played.time.as_secs_f64(), played.found);
+        recording::apply(&mut game, &played.found);
+        if game.status() == Status::Lost {
+            println!("move {} lost the game, replay with -n {} for the position before it", number + 1, number);
+        }
+    }
+    print!("{}", Position::from_game(&game));
 
+    if moves == recording.moves.len() && game.status() != recording.status {
+        return Err(Error::Recording(format!(
+            "the replay ended {} but the game was {}",
+            game.status().name(),
+            recording.status.name()
+        )));
+    }
     Ok(())
 }
 
+// plays a recorded game again with the solver, its policy and its seed, stopping at the first move that differs
+async fn rerun(args: &ReplayArgs, logger: Logger) -> Result<()> {
+    let recording = Recording::load(&args.file)?;
+    let seed = recording.seed.ok_or_else(|| Error::Recording("the seed of the game is unknown".to_string()))?;
+    let policy = PolicyKind::from_str(&recording.policy, false)
+        .map_err(|_| Error::Recording(format!("unknown policy {}", recording.policy)))?;
+    let opening = Opening::from_str(&recording.opening, false)
+        .map_err(|_| Error::Recording(format!("unknown opening {}", recording.opening)))?;
+
+    let mut board = Board::new(logger, recording.flags, recording.game()?);
+    board.set_policy(policy.build());
+    board.set_opening(opening);
+    board.set_seed(seed);
+    let report = board.play().await?;
+
+    let replayed = &board.recording().moves;
+    let differs = recording.moves.iter().zip(replayed).position(|(recorded, played)| recorded.found != played.found);
+    if let Some(index) = differs {
+        return Err(Error::Recording(format!(
+            "move {} differs, recorded {} but played {}",
+            index + 1,
+            recording.moves[index].found,
+            replayed[index].found
+        )));
+    }
+    if replayed.len() != recording.moves.len() || report.status != recording.status {
+        return Err(Error::Recording(format!(
+            "the game was {} after {} moves but the rerun {} after {}",
+            recording.status.name(),
+            recording.moves.len(),
+            report.status.name(),
+            replayed.len()
+        )));
+    }
+    println!("same {} moves, game {}", replayed.len(), report.status.name());
+    Ok(())
+}
+
+fn new_board<B: Backend>(args: &GameArgs, logger: Logger, backend: B, seed: u64) -> Board<B> {
+    let mut board = Board::new(logger, !args.no_flags, backend);
+    board.set_policy(args.policy.build());
+    board.set_opening(args.opening);
+    board.set_seed(seed);
+    board
+}
+
+fn save(recording: &Recording, directory: &Path, game: usize, logger: &Logger) -> Result<()> {
+    let path = directory.join(format!("game-{}-{}.json", recording.started, game));
+    recording.save(&path)?;
+    logger.log(
+        Level::Info,
+        "recording",
+        format_args!("recorded {} game in {}", recording.status.name(), path.display()),
+        || json!({ "status": recording.status.name(), "path": path.display().to_string() }),
+    );
+    Ok(())
+}
+
+// abandons the current game for a new session, after a pause that grows with every attempt
+async fn restart<B: Backend>(board: &mut Board<B>, error: Error, attempt: u32) -> Result<()> {
+    board.logger.log(
+        Level::Warn,
+        "restart",
+        format_args!("{}, restarting the session ({} of {})", error, attempt, MAX_RESTARTS),
+        || json!({ "error": error.to_string(), "attempt": attempt }),
+    );
+    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+    match board.recover().await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(error),
+        // the next game fails right away and gets another attempt
+        Err(failed) if failed.is_recoverable() => {
+            board.logger.log(Level::Warn, "restart", format_args!("restart failed: {}", failed), || {
+                json!({ "error": failed.to_string(), "attempt": attempt })
+            });
+            Ok(())
+        }
+        Err(failed) => Err(failed),
+    }
+}
+
+// starts a new game unless the board is fresh, then plays it
+async fn play_next<B: Backend>(board: &mut Board<B>, reset: bool) -> Result<GameReport> {
+    if reset {
+        board.reset_game().await?;
+    }
+    board.play().await
+}
+
+// plays until a game is won, or the given number of games
+async fn run<B: Backend>(board: &mut Board<B>, games: Option<usize>, args: &GameArgs) -> Result<Summary> {
+    let mut summary = Summary::default();
+    // restarts since the last finished game, so a browser that keeps failing still ends the batch
+    let mut restarts = 0;
+    // the last game is over and still on the board, a restart starts a new one by itself
+    let mut reset = false;
+
+    loop {
+        let report = match play_next(board, reset).await {
+            Ok(report) => report,
+            Err(error) if error.is_recoverable() && restarts < MAX_RESTARTS => {
+                restarts += 1;
+                restart(board, error, restarts).await?;
+                reset = false;
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        restarts = 0;
+        if !report.won() {
+            // the position right before the fatal click, ready to paste into a bug report
+            let (level, reason) = match report.loss {
+                Some(LossCause::Deduction) => (Level::Warn, "lost on a move that was supposed to be safe"),
+                _ => (Level::Debug, "lost on a guess"),
+            };
+            let position = board.position().to_string();
+            board.logger.log(level, "position", format_args!("{}, position before it:\n{}", reason, position.trim_end()), || {
+                json!({ "position": position })
+            });
+        }
+        if let Some(directory) = &args.record {
+            // a recording that cannot be written is no reason to stop the batch
+            if let Err(error) = save(board.recording(), directory, summary.games() + 1, &board.logger) {
+                board.logger.log(Level::Warn, "recording", format_args!("could not save the recording: {}", error), || {
+                    json!({ "error": error.to_string() })
+                });
+            }
+        }
+        let won = report.won();
+        summary.add(report);
+
+        match games {
+            None if won => break,
+            Some(games) if summary.games() >= games => break,
+            _ => reset = true,
+        }
+    }
+
+    Ok(summary)
+}
+
+// plays the same number of offline games with every opening, each from the same seed
+async fn compare_openings(args: &GameArgs, games: usize, logger: Logger) -> Result<()> {
+    let seed = choose_seed(args, &logger);
+    let game = offline_game(args, seed);
+    let first_click = game.first_click();
+    let auto = new_board(args, logger, game, seed).opening();
+
+    println!("{:<8} {:>8} {:>8} {:>8}", "opening", "won", "guesses", "clicks");
+    for opening in Opening::concrete() {
+        let mut board = new_board(args, logger, offline_game(args, seed), seed);
+        board.set_opening(opening);
+        let summary = run(&mut board, Some(games), args).await?;
+        println!(
+            "{:<8} {:>7.1}% {:>8.2} {:>8.1}",
+            opening.name(),
+            100.0 * summary.win_rate(),
+            summary.average_guesses(),
+            summary.average_clicks()
+        );
+    }
+    println!("auto opens with {} on {} when the first click is {}", auto.name(), args.difficulty, first_click.name());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+    use fantoccini::error::CmdError;
+    use crate::backend::Square;
+    use crate::difficulty::Difficulty;
+    use crate::posn::Posn;
+
+    // an offline game whose connection drops on the second click, once per game until it has no failures left,
+    // or when a new game is started
+    struct Flaky {
+        game: Game,
+        failures: u32,
+        // whether the current game already failed, a new session keeps playing it
+        failed: bool,
+        // fails in every new session too, like a browser that cannot start
+        broken: bool,
+        // resets that fail before a new game is started
+        reset_failures: u32,
+        clicks: Arc<AtomicUsize>,
+        recoveries: Arc<AtomicU32>,
+    }
+
+    fn lost() -> Error {
+        let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset");
+        Error::WebDriver(CmdError::Lost(reset))
+    }
+
+    impl Flaky {
+        fn new(failures: u32) -> Self {
+            Flaky {
+                game: Game::from_difficulty(Difficulty::Beginner, 5),
+                failures,
+                failed: false,
+                broken: false,
+                reset_failures: 0,
+                clicks: Arc::default(),
+                recoveries: Arc::default(),
+            }
+        }
+
+        fn click(&mut self) -> Result<()> {
+            if self.failures > 0 && !self.failed && self.clicks.load(Ordering::SeqCst) == 1 {
+                self.failures -= 1;
+                self.failed = !self.broken;
+                return Err(lost());
+            }
+            self.clicks.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    impl Backend for Flaky {
+        fn rows(&self) -> usize {
+            self.game.rows()
+        }
+
+        fn cols(&self) -> usize {
+            self.game.cols()
+        }
+
+        fn mines(&self) -> usize {
+            self.game.mines()
+        }
+
+        async fn reveal(&mut self, posn: Posn) -> Result<()> {
+            self.click()?;
+            Backend::reveal(&mut self.game, posn).await
+        }
+
+        async fn flag(&mut self, posn: Posn) -> Result<()> {
+            self.click()?;
+            Backend::flag(&mut self.game, posn).await
+        }
+
+        async fn chord(&mut self, posn: Posn) -> Result<()> {
+            self.click()?;
+            Backend::chord(&mut self.game, posn).await
+        }
+
+        async fn square(&self, posn: Posn) -> Result<Square> {
+            Backend::square(&self.game, posn).await
+        }
+
+        async fn status(&self) -> Result<Status> {
+            Backend::status(&self.game).await
+        }
+
+        async fn reset(&mut self) -> Result<()> {
+            if self.reset_failures > 0 {
+                self.reset_failures -= 1;
+                return Err(lost());
+            }
+            self.failed = false;
+            self.clicks.store(0, Ordering::SeqCst);
+            Backend::reset(&mut self.game).await
+        }
+
+        async fn recover(&mut self) -> Result<bool> {
+            self.recoveries.fetch_add(1, Ordering::SeqCst);
+            self.clicks.store(0, Ordering::SeqCst);
+            Backend::reset(&mut self.game).await?;
+            Ok(true)
+        }
+    }
+
+    fn bench_args() -> GameArgs {
+        match Cli::parse_from(["minesweeper-bot", "bench", "-b", "offline", "-d", "beginner"]).command {
+            Command::Bench(args) => args.game,
+            _ => panic!("expected bench"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_recovers_from_a_lost_session() {
+        let args = bench_args();
+        let backend = Flaky::new(3);
+        let (clicks, recoveries) = (backend.clicks.clone(), backend.recoveries.clone());
+        let mut board = new_board(&args, Logger::quiet(), backend, 11);
+
+        let summary = run(&mut board, Some(3), &args).await.unwrap();
+        assert_eq!(summary.games(), 3);
+        assert_eq!(recoveries.load(Ordering::SeqCst), 3);
+        // the last game was played after a restart, on a board that forgot the clicks of the one before
+        assert_eq!(board.recording().moves.len(), clicks.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_recovers_from_a_failed_reset() {
+        let args = bench_args();
+        let mut backend = Flaky::new(0);
+        backend.reset_failures = 2;
+        let recoveries = backend.recoveries.clone();
+        let mut board = new_board(&args, Logger::quiet(), backend, 11);
+
+        let summary = run(&mut board, Some(3), &args).await.unwrap();
+        assert_eq!(summary.games(), 3);
+        assert_eq!(recoveries.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_gives_up_after_max_restarts() {
+        let args = bench_args();
+        let mut backend = Flaky::new(u32::MAX);
+        backend.broken = true;
+        let recoveries = backend.recoveries.clone();
+        let mut board = new_board(&args, Logger::quiet(), backend, 11);
+
+        let result = run(&mut board, Some(3), &args).await;
+        assert!(matches!(result, Err(Error::WebDriver(_))));
+        assert_eq!(recoveries.load(Ordering::SeqCst), MAX_RESTARTS);
+    }
+}
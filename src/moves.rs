@@ -0,0 +1,223 @@
+use std::fmt;
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use crate::posn::Posn;
+use crate::solver::opening::Opening;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Flag,
+    Reveal,
+    // opens the blank neighbors of a satisfied number in one click
+    Chord,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Flag => "flag",
+            Action::Reveal => "reveal",
+            Action::Chord => "chord",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Action::Flag, Action::Reveal, Action::Chord].into_iter().find(|action| action.name() == name)
+    }
+}
+
+// why a move was made
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    // the number is missing as many mines as it has blank neighbors, or none at all
+    Count,
+    // the blank neighbors of the first number are shared with the second, which settles
+    // the cells only the second one sees
+    Subset,
+    // row reduction of the frontier's numbers leaves the cell a single value
+    Linear,
+    // every mine layout of the frontier component agrees
+    Enumeration { solutions: u64 },
+    // no mine layout of the frontier component has the cell the other way, found by search without counting them
+    Sat,
+    // of the frontier layouts, only the ones that leave room for the mines still unflagged fit
+    MineCount { mines: usize },
+    // the first click, before anything is open
+    Opening { opening: Opening },
+    // nothing is certain
+    Guess { probability: f64, forced: bool },
+}
+
+impl Rule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::Count => "count",
+            Rule::Subset => "subset",
+            Rule::Linear => "linear",
+            Rule::Enumeration { .. } => "enumeration",
+            Rule::Sat => "sat",
+            Rule::MineCount { .. } => "mine_count",
+            Rule::Opening { .. } => "opening",
+            Rule::Guess { .. } => "guess",
+        }
+    }
+}
+
+// a single click together with its justification
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub action: Action,
+    pub posn: Posn,
+    pub rule: Rule,
+    // the numbers the rule was applied to
+    pub numbers: Vec<Posn>,
+}
+
+impl Move {
+    pub fn new(action: Action, posn: Posn, rule: Rule, numbers: Vec<Posn>) -> Self {
+        Move { action, posn, rule, numbers }
+    }
+
+    pub fn is_guess(&self) -> bool {
+        matches!(self.rule, Rule::Guess { .. })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "action": self.action.name(),
+            "row": self.posn.row,
+            "col": self.posn.col,
+            "rule": self.rule.name(),
+            "numbers": self.numbers.iter().map(|posn| json!([posn.row, posn.col])).collect::<Vec<_>>(),
+        });
+        match self.rule {
+            Rule::Enumeration { solutions } => value["solutions"] = json!(solutions),
+            Rule::MineCount { mines } => value["mines"] = json!(mines),
+            Rule::Opening { opening } => value["opening"] = json!(opening.name()),
+            Rule::Guess { probability, forced } => {
+                value["probability"] = json!(probability);
+                value["forced"] = json!(forced);
+            }
+            Rule::Count | Rule::Subset | Rule::Linear | Rule::Sat => {}
+        }
+        value
+    }
+
+    // reads back what to_json wrote
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let posn = |row: &Value, col: &Value| Some(Posn::new(row.as_i64()? as i32, col.as_i64()? as i32));
+        let action = Action::from_name(value["action"].as_str()?)?;
+        let rule = match value["rule"].as_str()? {
+            "count" => Rule::Count,
+            "subset" => Rule::Subset,
+            "linear" => Rule::Linear,
+            "enumeration" => Rule::Enumeration { solutions: value["solutions"].as_u64()? },
+            "sat" => Rule::Sat,
+            "mine_count" => Rule::MineCount { mines: value["mines"].as_u64()? as usize },
+            "opening" => Rule::Opening { opening: Opening::from_str(value["opening"].as_str()?, false).ok()? },
+            "guess" => Rule::Guess {
+                probability: value["probability"].as_f64()?,
+                forced: value["forced"].as_bool()?,
+            },
+            _ => return None,
+        };
+        let numbers = value["numbers"]
+            .as_array()?
+            .iter()
+            .map(|number| posn(&number[0], &number[1]))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Move::new(action, posn(&value["row"], &value["col"])?, rule, numbers))
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let numbers = self.numbers.iter().map(Posn::coords).collect::<Vec<_>>().join(" ");
+        write!(f, "{} {}: ", self.action.name(), self.posn.coords())?;
+        match (self.rule, self.action) {
+            (Rule::Count, Action::Flag) => write!(f, "the number at {} is missing as many mines as it has blank neighbors", numbers),
+            (Rule::Count, Action::Chord) => write!(f, "every mine next to it is flagged, its blank neighbors are safe"),
+            (Rule::Count, _) => write!(f, "every mine next to the number at {} is flagged", numbers),
+            (Rule::Subset, action) => {
+                let (first, second) = match &self.numbers[..] {
+                    [first, second, ..] => (first.coords(), second.coords()),
+                    _ => return write!(f, "the numbers at {} overlap", numbers),
+                };
+                if action == Action::Flag {
+                    write!(f, "the number at {} is missing as many more mines than the one at {} as it has blank neighbors of its own", second, first)
+                } else {
+                    write!(f, "the number at {} is missing no more mines than the one at {}, whose blank neighbors it all sees", second, first)
+                }
+            }
+            (Rule::Linear, _) => write!(f, "adding and subtracting the numbers at {} leaves no other choice", numbers),
+            (Rule::Enumeration { solutions: 1 }, _) => write!(f, "the numbers at {} allow a single mine layout", numbers),
+            (Rule::Enumeration { solutions }, _) => write!(f, "all {} mine layouts the numbers at {} allow agree", solutions, numbers),
+            (Rule::Sat, Action::Flag) => write!(f, "the numbers at {} allow no mine layout where it is safe", numbers),
+            (Rule::Sat, _) => write!(f, "the numbers at {} allow no mine layout with a mine here", numbers),
+            (Rule::MineCount { mines: 1 }, Action::Flag) => write!(f, "1 mine is left, every way to place it puts it here"),
+            (Rule::MineCount { mines: 1 }, _) => write!(f, "1 mine is left, no way to place it puts it here"),
+            (Rule::MineCount { mines }, Action::Flag) => write!(f, "{} mines are left, every way to place them puts one here", mines),
+            (Rule::MineCount { mines }, _) => write!(f, "{} mines are left, no way to place them puts one here", mines),
+            (Rule::Opening { opening }, _) => write!(f, "nothing is open yet, {} opening", opening.name()),
+            (Rule::Guess { probability, forced }, _) => {
+                write!(f, "nothing is certain, {:.1}% chance of a mine", 100.0 * probability)?;
+                if forced {
+                    write!(f, ", no safe reveal elsewhere can settle this region")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let flag = Move::new(Action::Flag, Posn::new(0, 1), Rule::Count, vec![Posn::new(1, 1)]);
+        assert_eq!(flag.to_string(), "flag (2, 1): the number at (2, 2) is missing as many mines as it has blank neighbors");
+
+        let guess = Move::new(Action::Reveal, Posn::new(2, 0), Rule::Guess { probability: 0.5, forced: true }, vec![]);
+        assert_eq!(guess.to_string(), "reveal (1, 3): nothing is certain, 50.0% chance of a mine, no safe reveal elsewhere can settle this region");
+        assert!(guess.is_guess());
+
+        let last = Move::new(Action::Reveal, Posn::new(3, 3), Rule::MineCount { mines: 1 }, vec![]);
+        assert_eq!(last.to_string(), "reveal (4, 4): 1 mine is left, no way to place it puts it here");
+        let more = Move::new(Action::Flag, Posn::new(3, 3), Rule::MineCount { mines: 2 }, vec![]);
+        assert_eq!(more.to_string(), "flag (4, 4): 2 mines are left, every way to place them puts one here");
+
+        let subset = Move::new(Action::Reveal, Posn::new(0, 3), Rule::Subset, vec![Posn::new(1, 0), Posn::new(1, 1)]);
+        assert_eq!(
+            subset.to_string(),
+            "reveal (4, 1): the number at (2, 2) is missing no more mines than the one at (1, 2), whose blank neighbors it all sees"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let reveal = Move::new(
+            Action::Reveal,
+            Posn::new(3, 4),
+            Rule::Enumeration { solutions: 6 },
+            vec![Posn::new(2, 4), Posn::new(2, 5)],
+        );
+        assert_eq!(
+            reveal.to_json(),
+            json!({
+                "action": "reveal",
+                "row": 3,
+                "col": 4,
+                "rule": "enumeration",
+                "numbers": [[2, 4], [2, 5]],
+                "solutions": 6,
+            })
+        );
+        assert_eq!(Move::from_json(&reveal.to_json()), Some(reveal));
+
+        let guess = Move::new(Action::Reveal, Posn::new(0, 0), Rule::Guess { probability: 0.25, forced: false }, vec![]);
+        assert_eq!(Move::from_json(&guess.to_json()), Some(guess));
+        assert_eq!(Move::from_json(&json!({ "action": "dig", "row": 0, "col": 0 })), None);
+    }
+}
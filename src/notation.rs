@@ -0,0 +1,185 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::backend::Square;
+use crate::engine::Game;
+use crate::error::{Error, Result};
+use crate::posn::Posn;
+
+// a board position as plain text, for bug reports and regression tests
+//
+//   size 3x4
+//   mines 2
+//   __f1
+//   1221
+//   0000
+//
+// squares use the characters of info::get_reps: '_' blank, 'f' flag, '0'-'8' opened,
+// plus '*' for a mine shown after a loss. empty lines and lines starting with '#' are skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub rows: usize,
+    pub cols: usize,
+    pub mines: usize,
+    squares: Vec<Vec<Square>>,
+}
+
+impl Position {
+    // a position with nothing opened yet
+    pub fn new(rows: usize, cols: usize, mines: usize) -> Self {
+        Position {
+            rows,
+            cols,
+            mines,
+            squares: vec![vec![Square::Hidden; cols]; rows],
+        }
+    }
+
+    // what a player sees of an offline game
+    pub fn from_game(game: &Game) -> Self {
+        let mut position = Position::new(game.rows(), game.cols(), game.mines());
+        for row in 0..game.rows() {
+            for col in 0..game.cols() {
+                let posn = Posn::new(row as i32, col as i32);
+                position.set_square(posn, game.square(posn));
+            }
+        }
+        position
+    }
+
+    pub fn square(&self, posn: Posn) -> Square {
+        self.squares[posn.row as usize][posn.col as usize]
+    }
+
+    pub fn set_square(&mut self, posn: Posn, square: Square) {
+        self.squares[posn.row as usize][posn.col as usize] = square;
+    }
+
+    pub fn squares(&self) -> &[Vec<Square>] {
+        &self.squares
+    }
+}
+
+pub fn square_char(square: Square) -> char {
+    match square {
+        Square::Hidden => '_',
+        Square::Flagged => 'f',
+        Square::Open(digit) => (b'0' + digit) as char,
+        Square::Mine | Square::Exploded => '*',
+    }
+}
+
+pub fn char_square(rep: char) -> Option<Square> {
+    match rep {
+        '_' => Some(Square::Hidden),
+        'f' => Some(Square::Flagged),
+        '*' => Some(Square::Mine),
+        '0'..='8' => Some(Square::Open(rep as u8 - b'0')),
+        _ => None,
+    }
+}
+
+fn parse_error(line: usize, message: String) -> Error {
+    Error::Notation(format!("line {}: {}", line + 1, message))
+}
+
+impl FromStr for Position {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut size = None;
+        let mut mines = None;
+        let mut squares: Vec<Vec<Square>> = vec![];
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("size") {
+                let dimensions: Vec<usize> = value
+                    .trim()
+                    .split('x')
+                    .map(|part| part.trim().parse::<usize>())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|_| parse_error(number, format!("size should look like ROWSxCOLS, got {}", value.trim())))?;
+                match dimensions[..] {
+                    [rows, cols] if rows > 0 && cols > 0 => size = Some((rows, cols)),
+                    _ => return Err(parse_error(number, format!("size should look like ROWSxCOLS, got {}", value.trim()))),
+                }
+            } else if let Some(value) = line.strip_prefix("mines") {
+                let count = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| parse_error(number, format!("mines should be a number, got {}", value.trim())))?;
+                mines = Some(count);
+            } else {
+                let row = line
+                    .chars()
+                    .map(|rep| char_square(rep).ok_or_else(|| parse_error(number, format!("unknown square {:?}", rep))))
+                    .collect::<Result<Vec<_>>>()?;
+                squares.push(row);
+            }
+        }
+
+        let (rows, cols) = size.ok_or_else(|| Error::Notation("missing size line".to_string()))?;
+        let mines = mines.ok_or_else(|| Error::Notation("missing mines line".to_string()))?;
+        if squares.len() != rows {
+            return Err(Error::Notation(format!("expected {} rows, got {}", rows, squares.len())));
+        }
+        if let Some(row) = squares.iter().position(|row| row.len() != cols) {
+            return Err(Error::Notation(format!("row {} should have {} squares, got {}", row + 1, cols, squares[row].len())));
+        }
+        if mines >= rows * cols {
+            return Err(Error::Notation(format!("{} mines do not fit on a {}x{} board", mines, rows, cols)));
+        }
+
+        Ok(Position { rows, cols, mines, squares })
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "size {}x{}", self.rows, self.cols)?;
+        writeln!(f, "mines {}", self.mines)?;
+        for row in &self.squares {
+            writeln!(f, "{}", row.iter().map(|&square| square_char(square)).collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::webdriver::parse_square;
+    use crate::info;
+
+    const POSITION: &str = "# a 1-2-1 against the top edge\nsize 3x4\nmines 2\n\n__f1\n1221\n0000\n";
+
+    #[test]
+    fn test_round_trip() {
+        let position: Position = POSITION.parse().unwrap();
+        assert_eq!((position.rows, position.cols, position.mines), (3, 4, 2));
+        assert_eq!(position.square(Posn::new(0, 2)), Square::Flagged);
+        assert_eq!(position.square(Posn::new(1, 1)), Square::Open(2));
+        assert_eq!(position.to_string(), "size 3x4\nmines 2\n__f1\n1221\n0000\n");
+        assert_eq!(position.to_string().parse::<Position>().unwrap(), position);
+    }
+
+    #[test]
+    fn test_invalid_positions() {
+        assert!("mines 2\n__\n".parse::<Position>().is_err());
+        assert!("size 2x2\n__\n__\n".parse::<Position>().is_err());
+        assert!("size 2x2\nmines 1\n__\n_\n".parse::<Position>().is_err());
+        assert!("size 1x2\nmines 1\n_9\n".parse::<Position>().is_err());
+        assert!("size 1x2\nmines 2\n__\n".parse::<Position>().is_err());
+    }
+
+    #[test]
+    fn test_characters_match_the_site() {
+        for (class, rep) in info::get_reps() {
+            assert_eq!(char_square(rep), parse_square(class));
+        }
+    }
+}
@@ -1,19 +1,23 @@
+use std::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Posn {
     pub row: i32,
     pub col: i32,
 }
 
+// string representation of the position
+impl fmt::Display for Posn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Posn(row = {}, col = {})", self.row, self.col)
+    }
+}
+
 impl Posn {
     pub fn new(row: i32, col: i32) -> Self {
         Posn { row, col }
     }
 
-    // string representation of the position
-    pub fn to_string(&self) -> String {
-        format!("Posn(row = {}, col = {})", self.row, self.col)
-    }
-
     // string representation of coordinates
     pub fn coords(&self) -> String {
         format!("({}, {})", self.col + 1, self.row + 1)
@@ -39,11 +43,10 @@ impl Posn {
     
     // returns 8 blocks surrounding the position that are in range
     pub fn surrounding_in_range(&self, rows: i32, cols: i32) -> Vec<Posn> {
-        let test = self.surrounding()
+        self.surrounding()
             .into_iter()
             .filter(|posn| posn.in_range(rows, cols))
-            .collect();
-        test
+            .collect()
     }
 }
 
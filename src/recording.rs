@@ -0,0 +1,232 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use crate::backend::Status;
+use crate::engine::Game;
+use crate::error::{Error, Result};
+use crate::moves::{Action, Move};
+use crate::posn::Posn;
+
+// a move together with the time it was played, counted from the start of the game
+#[derive(Debug, Clone, PartialEq)]
+pub struct Played {
+    pub time: Duration,
+    pub found: Move,
+}
+
+// one game as the bot played it, enough to play it again offline once the mines are known
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub rows: usize,
+    pub cols: usize,
+    pub mines: usize,
+    // seconds since the unix epoch
+    pub started: u64,
+    // what the solver guessed from and how it played, to play the game again move for move
+    pub seed: Option<u64>,
+    pub policy: String,
+    pub opening: String,
+    pub flags: bool,
+    pub moves: Vec<Played>,
+    pub status: Status,
+    // every mine, only known once the game is over
+    pub mine_layout: Option<Vec<Posn>>,
+}
+
+fn posn_json(posn: &Posn) -> Value {
+    json!([posn.row, posn.col])
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Recording(message.to_string())
+}
+
+impl Recording {
+    pub fn new(rows: usize, cols: usize, mines: usize) -> Self {
+        Recording {
+            rows,
+            cols,
+            mines,
+            started: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()),
+            seed: None,
+            policy: "informed".to_string(),
+            opening: "policy".to_string(),
+            flags: true,
+            moves: vec![],
+            status: Status::Playing,
+            mine_layout: None,
+        }
+    }
+
+    pub fn push(&mut self, time: Duration, found: Move) {
+        self.moves.push(Played { time, found });
+    }
+
+    pub fn finish(&mut self, status: Status, mine_layout: Option<Vec<Posn>>) {
+        self.status = status;
+        self.mine_layout = mine_layout;
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "rows": self.rows,
+            "cols": self.cols,
+            "mines": self.mines,
+            "started": self.started,
+            "seed": self.seed,
+            "policy": self.policy,
+            "opening": self.opening,
+            "flags": self.flags,
+            "status": self.status.name(),
+            "mine_layout": self.mine_layout.as_ref().map(|layout| layout.iter().map(posn_json).collect::<Vec<_>>()),
+            "moves": self
+                .moves
+                .iter()
+                .map(|played| {
+                    let mut value = played.found.to_json();
+                    value["time"] = json!(played.time.as_secs_f64());
+                    value
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let count = |key: &str| {
+            value[key]
+                .as_u64()
+                .map(|count| count as usize)
+                .ok_or_else(|| Error::Recording(format!("missing {}", key)))
+        };
+        let (rows, cols, mines) = (count("rows")?, count("cols")?, count("mines")?);
+        let status = [Status::Playing, Status::Won, Status::Lost]
+            .into_iter()
+            .find(|&status| value["status"].as_str() == Some(status.name()))
+            .ok_or_else(|| invalid("missing status"))?;
+
+        let mine_layout = match &value["mine_layout"] {
+            Value::Null => None,
+            layout => {
+                let layout = layout
+                    .as_array()
+                    .ok_or_else(|| invalid("mine_layout should be a list"))?
+                    .iter()
+                    .map(|posn| Some(Posn::new(posn[0].as_i64()? as i32, posn[1].as_i64()? as i32)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| invalid("mines should look like [row, col]"))?;
+                if let Some(posn) = layout.iter().find(|posn| !posn.in_range(rows as i32, cols as i32)) {
+                    return Err(Error::Recording(format!("mine at {} is off the board", posn.coords())));
+                }
+                Some(layout)
+            }
+        };
+
+        let moves = value["moves"]
+            .as_array()
+            .ok_or_else(|| invalid("missing moves"))?
+            .iter()
+            .enumerate()
+            .map(|(number, played)| {
+                let found = Move::from_json(played).filter(|found| found.posn.in_range(rows as i32, cols as i32));
+                match (found, played["time"].as_f64()) {
+                    (Some(found), Some(time)) if time >= 0.0 => Ok(Played { time: Duration::from_secs_f64(time), found }),
+                    _ => Err(Error::Recording(format!("move {} is not a valid move", number + 1))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Recording {
+            rows,
+            cols,
+            mines,
+            started: value["started"].as_u64().unwrap_or(0),
+            seed: value["seed"].as_u64(),
+            policy: value["policy"].as_str().unwrap_or("informed").to_string(),
+            // recordings from before openings left the first click to the policy
+            opening: value["opening"].as_str().unwrap_or("policy").to_string(),
+            flags: value["flags"].as_bool().unwrap_or(true),
+            moves,
+            status,
+            mine_layout,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, format!("{}\n", self.to_json()))?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let value: Value = serde_json::from_str(&text).map_err(|error| Error::Recording(error.to_string()))?;
+        Self::from_json(&value)
+    }
+
+    // an offline game with the recorded mines and nothing opened yet
+    pub fn game(&self) -> Result<Game> {
+        let layout = self
+            .mine_layout
+            .as_ref()
+            .ok_or_else(|| invalid("the mine layout is unknown, the game was never finished"))?;
+        if layout.len() >= self.rows * self.cols {
+            return Err(invalid("the mines leave no safe square"));
+        }
+        Ok(Game::with_mines(self.rows, self.cols, layout))
+    }
+}
+
+// plays a recorded move on an offline game, flags included so later chords behave the same
+pub fn apply(game: &mut Game, found: &Move) {
+    match found.action {
+        Action::Flag => {
+            game.flag(found.posn);
+        }
+        Action::Reveal => {
+            game.reveal(found.posn);
+        }
+        Action::Chord => {
+            game.chord(found.posn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::Rule;
+
+    #[test]
+    fn test_round_trip_and_replay() {
+        let mut recording = Recording::new(3, 3, 1);
+        let numbers = vec![Posn::new(1, 1)];
+        let probability = 13.0 / 63.0;
+        recording.push(Duration::ZERO, Move::new(Action::Reveal, Posn::new(1, 1), Rule::Guess { probability, forced: false }, vec![]));
+        recording.push(Duration::from_millis(250), Move::new(Action::Flag, Posn::new(0, 0), Rule::Count, numbers.clone()));
+        recording.push(Duration::from_millis(500), Move::new(Action::Chord, Posn::new(1, 1), Rule::Count, numbers));
+        recording.finish(Status::Won, Some(vec![Posn::new(0, 0)]));
+        recording.seed = Some(u64::MAX);
+
+        // through text, a rerun compares probabilities bit for bit
+        let read = Recording::from_json(&serde_json::from_str(&recording.to_json().to_string()).unwrap()).unwrap();
+        assert_eq!(read, recording);
+
+        let mut game = read.game().unwrap();
+        for played in &read.moves {
+            apply(&mut game, &played.found);
+        }
+        assert_eq!(game.status(), Status::Won);
+    }
+
+    #[test]
+    fn test_invalid_recordings() {
+        let recording = Recording::new(3, 3, 1);
+        assert!(recording.game().is_err());
+
+        let mut value = recording.to_json();
+        value["mine_layout"] = json!([[3, 0]]);
+        assert!(Recording::from_json(&value).is_err());
+        value["mine_layout"] = json!(null);
+        value["moves"] = json!([{ "action": "reveal", "row": 0, "col": 0, "rule": "count", "numbers": [] }]);
+        assert!(Recording::from_json(&value).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// what a seed is drawn for. one seed drives a whole run, each use gets its own stream
+// so the guesses have nothing in common with the layouts they are made on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    // mine layouts of the offline engine
+    Layouts,
+    // the seeds of the games after the first
+    Games,
+    // a game's guesses and random openings
+    Guesses,
+}
+
+impl Stream {
+    fn salt(self) -> u64 {
+        match self {
+            Stream::Layouts => 0x6c61_796f_7574_7321,
+            Stream::Games => 0x6761_6d65_7321_2121,
+            Stream::Guesses => 0x6775_6573_7365_7321,
+        }
+    }
+}
+
+// the generator for one use of a seed, the same seed and stream always give the same numbers
+pub fn rng(seed: u64, stream: Stream) -> StdRng {
+    StdRng::seed_from_u64(seed ^ stream.salt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn draws(seed: u64, stream: Stream) -> Vec<u64> {
+        let mut rng = rng(seed, stream);
+        (0..8).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_streams_differ() {
+        for seed in [0, 1, 11, u64::MAX] {
+            assert_eq!(draws(seed, Stream::Layouts), draws(seed, Stream::Layouts));
+            assert_ne!(draws(seed, Stream::Layouts), draws(seed, Stream::Guesses));
+            assert_ne!(draws(seed, Stream::Layouts), draws(seed, Stream::Games));
+            assert_ne!(draws(seed, Stream::Games), draws(seed, Stream::Guesses));
+        }
+    }
+}
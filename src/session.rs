@@ -0,0 +1,113 @@
+use std::str::FromStr;
+use fantoccini::{Client, ClientBuilder};
+use serde_json::{json, Map, Value};
+use tokio::time::{timeout, Duration};
+use crate::error::Result;
+
+// how long a dead session gets to close before it is dropped
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// browser window size in pixels, "WIDTHxHEIGHT"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for WindowSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parts: Vec<u32> = s
+            .split('x')
+            .map(|part| part.trim().parse::<u32>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| format!("window size should look like WIDTHxHEIGHT, got {}", s))?;
+        match parts[..] {
+            [width, height] if width > 0 && height > 0 => Ok(WindowSize { width, height }),
+            _ => Err(format!("window size should look like WIDTHxHEIGHT, got {}", s)),
+        }
+    }
+}
+
+// everything needed to start a browser session, kept around to start it again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionOptions {
+    pub webdriver: String,
+    // the page the game is played on, the real site or a local copy of it
+    pub site: String,
+    pub headless: bool,
+    pub window_size: Option<WindowSize>,
+    // passed to chrome and firefox alike, each ignores what it does not know
+    pub browser_args: Vec<String>,
+}
+
+impl SessionOptions {
+    pub fn capabilities(&self) -> Map<String, Value> {
+        let mut chrome_args = self.browser_args.clone();
+        let mut firefox_args = self.browser_args.clone();
+        if self.headless {
+            chrome_args.push("--headless=new".to_string());
+            firefox_args.push("-headless".to_string());
+        }
+        // a headless browser starts with a tiny window unless told otherwise
+        if let Some(size) = self.window_size {
+            chrome_args.push(format!("--window-size={},{}", size.width, size.height));
+            firefox_args.extend([format!("--width={}", size.width), format!("--height={}", size.height)]);
+        }
+
+        let mut capabilities = Map::new();
+        if !chrome_args.is_empty() {
+            capabilities.insert("goog:chromeOptions".to_string(), json!({ "args": chrome_args }));
+            capabilities.insert("moz:firefoxOptions".to_string(), json!({ "args": firefox_args }));
+        }
+        capabilities
+    }
+
+    pub async fn connect(&self) -> Result<Client> {
+        let client = ClientBuilder::native()
+            .capabilities(self.capabilities())
+            .connect(&self.webdriver)
+            .await?;
+        if let Some(size) = self.window_size {
+            client.set_window_size(size.width, size.height).await?;
+        }
+        Ok(client)
+    }
+}
+
+// ends a session, giving up quietly when the browser is already gone
+pub async fn close(client: Client) {
+    let _ = timeout(CLOSE_TIMEOUT, client.close()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(headless: bool, window_size: Option<WindowSize>) -> SessionOptions {
+        SessionOptions {
+            webdriver: "http://localhost:9515".to_string(),
+            site: "http://localhost:8000/".to_string(),
+            headless,
+            window_size,
+            browser_args: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_window_size() {
+        assert_eq!("1280x800".parse(), Ok(WindowSize { width: 1280, height: 800 }));
+        assert!("1280".parse::<WindowSize>().is_err());
+        assert!("0x800".parse::<WindowSize>().is_err());
+    }
+
+    #[test]
+    fn test_capabilities() {
+        assert!(options(false, None).capabilities().is_empty());
+
+        let capabilities = options(true, Some(WindowSize { width: 1280, height: 800 })).capabilities();
+        assert_eq!(capabilities["goog:chromeOptions"], json!({ "args": ["--headless=new", "--window-size=1280,800"] }));
+        assert_eq!(capabilities["moz:firefoxOptions"], json!({ "args": ["-headless", "--width=1280", "--height=800"] }));
+    }
+}
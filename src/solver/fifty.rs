@@ -0,0 +1,118 @@
+use std::collections::{HashMap, HashSet};
+use crate::posn::Posn;
+use crate::solver::frontier::Frontier;
+
+// frontier cells that no other reveal can tell anything about, so one of them
+// has to be guessed sooner or later (the classic 50/50 and its larger cousins)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub cells: Vec<Posn>,
+    pub probabilities: HashMap<Posn, f64>,
+}
+
+// every frontier component that is a true coin flip
+pub fn forced_guesses(frontier: &Frontier, hidden: &HashSet<Posn>, rows: usize, cols: usize) -> Vec<Region> {
+    let mut regions = vec![];
+
+    for (component_index, component) in frontier.components().iter().enumerate() {
+        let cells: HashSet<Posn> = component.cells.iter().copied().collect();
+
+        // a hidden neighbor outside the component could still reveal a useful number
+        let enclosed = component.cells.iter().all(|cell| {
+            cell.surrounding_in_range(rows as i32, cols as i32)
+                .iter()
+                .all(|neighbor| !hidden.contains(neighbor) || cells.contains(neighbor))
+        });
+        if !enclosed {
+            continue;
+        }
+
+        let Some(solutions) = frontier.solutions(component_index) else {
+            continue;
+        };
+        // configurations with different mine counts can be told apart by the counter
+        if solutions.by_mines.iter().filter(|tally| tally.count > 0).count() != 1 {
+            continue;
+        }
+        let count = solutions.count();
+        let undecided = (0..component.cells.len()).all(|index| {
+            let mine_count = solutions.mine_count(index);
+            mine_count > 0 && mine_count < count
+        });
+        if !undecided {
+            continue;
+        }
+
+        let probabilities = component.cells
+            .iter()
+            .enumerate()
+            .map(|(index, &cell)| (cell, solutions.mine_count(index) as f64 / count as f64))
+            .collect();
+        regions.push(Region {
+            cells: component.cells.clone(),
+            probabilities,
+        });
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Constraint;
+
+    fn forced(constraints: &[Constraint], hidden: &HashSet<Posn>, rows: usize, cols: usize) -> Vec<Region> {
+        forced_guesses(&Frontier::new(constraints), hidden, rows, cols)
+    }
+
+    fn hidden(posns: &[Posn]) -> HashSet<Posn> {
+        posns.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_two_cells_at_the_wall() {
+        // 1 1 under two hidden cells in the top row of a 2x2 board
+        let (a, b) = (Posn::new(0, 0), Posn::new(0, 1));
+        let constraints = vec![Constraint::new(vec![a, b], 1), Constraint::new(vec![a, b], 1)];
+        let regions = forced(&constraints, &hidden(&[a, b]), 2, 2);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].probabilities[&a], 0.5);
+    }
+
+    #[test]
+    fn test_open_neighbor_can_still_help() {
+        let (a, b, c) = (Posn::new(0, 0), Posn::new(0, 1), Posn::new(0, 2));
+        let constraints = vec![Constraint::new(vec![a, b], 1)];
+        assert!(forced(&constraints, &hidden(&[a, b, c]), 2, 3).is_empty());
+    }
+
+    #[test]
+    fn test_square_of_four() {
+        // a b / c d with one mine per row and per column
+        let (a, b, c, d) = (Posn::new(0, 0), Posn::new(0, 1), Posn::new(1, 0), Posn::new(1, 1));
+        let constraints = vec![
+            Constraint::new(vec![a, b], 1),
+            Constraint::new(vec![c, d], 1),
+            Constraint::new(vec![a, c], 1),
+            Constraint::new(vec![b, d], 1),
+        ];
+        let regions = forced(&constraints, &hidden(&[a, b, c, d]), 3, 3);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].cells.len(), 4);
+    }
+
+    #[test]
+    fn test_mine_count_can_decide() {
+        // either a alone or both b and c
+        let (a, b, c) = (Posn::new(0, 1), Posn::new(0, 0), Posn::new(0, 2));
+        let constraints = vec![Constraint::new(vec![a, b], 1), Constraint::new(vec![a, c], 1)];
+        assert!(forced(&constraints, &hidden(&[a, b, c]), 2, 3).is_empty());
+    }
+
+    #[test]
+    fn test_certain_cells_are_not_guesses() {
+        let (a, b) = (Posn::new(0, 0), Posn::new(0, 1));
+        let constraints = vec![Constraint::new(vec![a, b], 1), Constraint::new(vec![b], 0)];
+        assert!(forced(&constraints, &hidden(&[a, b]), 2, 2).is_empty());
+    }
+}
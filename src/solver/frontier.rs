@@ -0,0 +1,351 @@
+use std::cell::OnceCell;
+use std::collections::{HashMap, VecDeque};
+use crate::error::{Error, Result};
+use crate::posn::Posn;
+use crate::solver::Constraint;
+
+// upper bound on search nodes per component before the enumeration gives up
+const SEARCH_LIMIT: usize = 1 << 20;
+
+// blank cells bordering numbers that influence each other, with the numbers touching them
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub cells: Vec<Posn>,
+    // constraints over local cell indices
+    constraints: Vec<(Vec<usize>, usize)>,
+}
+
+// consistent mine assignments of a component that use the same number of mines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally {
+    pub count: u64,
+    // for each cell, in how many of these assignments it is a mine
+    pub mine_counts: Vec<u64>,
+}
+
+// every consistent mine assignment of a component, indexed by how many mines it uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solutions {
+    pub by_mines: Vec<Tally>,
+}
+
+impl Solutions {
+    pub fn count(&self) -> u64 {
+        self.by_mines.iter().map(|tally| tally.count).sum()
+    }
+
+    pub fn mine_count(&self, cell: usize) -> u64 {
+        self.by_mines.iter().map(|tally| tally.mine_counts[cell]).sum()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deductions {
+    pub mines: Vec<Posn>,
+    pub safe: Vec<Posn>,
+    // number of mine layouts of the component each deduced cell belongs to
+    pub solutions: HashMap<Posn, u64>,
+}
+
+// partitions the constrained cells into independent connected components
+pub fn components(constraints: &[Constraint]) -> Vec<Component> {
+    let mut by_cell: HashMap<Posn, Vec<usize>> = HashMap::new();
+    for (index, constraint) in constraints.iter().enumerate() {
+        for &cell in &constraint.cells {
+            by_cell.entry(cell).or_default().push(index);
+        }
+    }
+
+    let mut seen_constraints = vec![false; constraints.len()];
+    let mut components = vec![];
+
+    for start in 0..constraints.len() {
+        if seen_constraints[start] || constraints[start].cells.is_empty() {
+            continue;
+        }
+        seen_constraints[start] = true;
+
+        // bfs over constraints sharing a cell, cells are kept in discovery order
+        let mut cells: Vec<Posn> = vec![];
+        let mut local: HashMap<Posn, usize> = HashMap::new();
+        let mut members = vec![];
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(index) = queue.pop_front() {
+            members.push(index);
+            for &cell in &constraints[index].cells {
+                if local.contains_key(&cell) {
+                    continue;
+                }
+                local.insert(cell, cells.len());
+                cells.push(cell);
+                for &other in &by_cell[&cell] {
+                    if !seen_constraints[other] {
+                        seen_constraints[other] = true;
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+
+        let constraints = members
+            .into_iter()
+            .map(|index| {
+                let constraint = &constraints[index];
+                let indices = constraint.cells.iter().map(|cell| local[cell]).collect();
+                (indices, constraint.mines)
+            })
+            .collect();
+        components.push(Component { cells, constraints });
+    }
+    components
+}
+
+impl Component {
+    // the constraints as local cell indices and their mine counts
+    pub fn constraints(&self) -> &[(Vec<usize>, usize)] {
+        &self.constraints
+    }
+
+    // enumerates every consistent mine assignment, None if the search is too large
+    pub fn enumerate(&self) -> Option<Solutions> {
+        let mut search = Search::new(self);
+        search.run(0)?;
+        Some(Solutions { by_mines: search.by_mines })
+    }
+}
+
+// how far each constraint of a component is from being met while its cells get assigned one at a time,
+// the bookkeeping shared by every search over a component
+pub struct Counts<'a> {
+    component: &'a Component,
+    cell_constraints: Vec<Vec<usize>>,
+    // mines placed and cells still unassigned per constraint
+    placed: Vec<usize>,
+    unassigned: Vec<usize>,
+}
+
+impl<'a> Counts<'a> {
+    pub fn new(component: &'a Component) -> Self {
+        let mut cell_constraints = vec![vec![]; component.cells.len()];
+        for (index, (cells, _)) in component.constraints.iter().enumerate() {
+            for &cell in cells {
+                cell_constraints[cell].push(index);
+            }
+        }
+        Counts {
+            component,
+            cell_constraints,
+            placed: vec![0; component.constraints.len()],
+            unassigned: component.constraints.iter().map(|(cells, _)| cells.len()).collect(),
+        }
+    }
+
+    // assigns a cell and reports whether every constraint touching it can still hold
+    pub fn assign(&mut self, cell: usize, mine: bool) -> bool {
+        let mut consistent = true;
+        for &index in &self.cell_constraints[cell] {
+            self.unassigned[index] -= 1;
+            if mine {
+                self.placed[index] += 1;
+            }
+            let target = self.component.constraints[index].1;
+            if self.placed[index] > target || self.placed[index] + self.unassigned[index] < target {
+                consistent = false;
+            }
+        }
+        consistent
+    }
+
+    pub fn unassign(&mut self, cell: usize, mine: bool) {
+        for &index in &self.cell_constraints[cell] {
+            self.unassigned[index] += 1;
+            if mine {
+                self.placed[index] -= 1;
+            }
+        }
+    }
+}
+
+// what the SAT search needs to propagate, the enumeration only ever checks consistency
+#[cfg(feature = "sat")]
+impl<'a> Counts<'a> {
+    pub fn component(&self) -> &'a Component {
+        self.component
+    }
+
+    // indices of the constraints a cell appears in
+    pub fn constraints_of(&self, cell: usize) -> &[usize] {
+        &self.cell_constraints[cell]
+    }
+
+    // what the unassigned cells of a constraint must all be once it has only one way left to be met
+    pub fn forced(&self, index: usize) -> Option<bool> {
+        let target = self.component.constraints[index].1;
+        if self.unassigned[index] == 0 {
+            None
+        } else if self.placed[index] == target {
+            Some(false)
+        } else if self.placed[index] + self.unassigned[index] == target {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+// backtracking state, constraints are checked as soon as a cell is assigned
+struct Search<'a> {
+    counts: Counts<'a>,
+    assignment: Vec<bool>,
+    mines: usize,
+    by_mines: Vec<Tally>,
+    nodes: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(component: &'a Component) -> Self {
+        let empty = Tally {
+            count: 0,
+            mine_counts: vec![0; component.cells.len()],
+        };
+
+        Search {
+            counts: Counts::new(component),
+            assignment: vec![false; component.cells.len()],
+            mines: 0,
+            by_mines: vec![empty; component.cells.len() + 1],
+            nodes: 0,
+        }
+    }
+
+    fn run(&mut self, cell: usize) -> Option<()> {
+        self.nodes += 1;
+        if self.nodes > SEARCH_LIMIT {
+            return None;
+        }
+
+        if cell == self.assignment.len() {
+            let tally = &mut self.by_mines[self.mines];
+            tally.count += 1;
+            for (index, &mine) in self.assignment.iter().enumerate() {
+                if mine {
+                    tally.mine_counts[index] += 1;
+                }
+            }
+            return Some(());
+        }
+
+        for mine in [false, true] {
+            self.assignment[cell] = mine;
+            self.mines += mine as usize;
+            if self.counts.assign(cell, mine) {
+                self.run(cell + 1)?;
+            }
+            self.counts.unassign(cell, mine);
+            self.mines -= mine as usize;
+        }
+        self.assignment[cell] = false;
+        Some(())
+    }
+}
+
+// the components of the frontier, each enumerated the first time something asks for its layouts,
+// so every rule of a step shares one enumeration
+pub struct Frontier {
+    components: Vec<Component>,
+    solutions: Vec<OnceCell<Option<Solutions>>>,
+}
+
+impl Frontier {
+    pub fn new(constraints: &[Constraint]) -> Self {
+        let components = components(constraints);
+        let solutions = components.iter().map(|_| OnceCell::new()).collect();
+        Frontier { components, solutions }
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    // every layout of a component, None when there are too many to enumerate
+    pub fn solutions(&self, index: usize) -> Option<&Solutions> {
+        self.solutions[index].get_or_init(|| self.components[index].enumerate()).as_ref()
+    }
+
+    // every component with its layouts
+    pub fn solved(&self) -> impl Iterator<Item = (&Component, Option<&Solutions>)> {
+        (0..self.components.len()).map(|index| (&self.components[index], self.solutions(index)))
+    }
+
+    // the components the enumeration gives up on
+    #[cfg(feature = "sat")]
+    pub fn unenumerated(&self) -> impl Iterator<Item = &Component> {
+        self.solved().filter_map(|(component, solutions)| solutions.is_none().then_some(component))
+    }
+
+    // every cell that is a mine in all solutions or safe in all solutions
+    pub fn deductions(&self) -> Result<Deductions> {
+        let mut deductions = Deductions::default();
+        for (component, solutions) in self.solved() {
+            let Some(solutions) = solutions else {
+                continue;
+            };
+            let count = solutions.count();
+            if count == 0 {
+                return Err(Error::Contradiction(format!(
+                    "no mine layout satisfies the numbers around {}",
+                    component.cells[0].coords()
+                )));
+            }
+            for (index, &cell) in component.cells.iter().enumerate() {
+                let mine_count = solutions.mine_count(index);
+                if mine_count == count {
+                    deductions.mines.push(cell);
+                } else if mine_count == 0 {
+                    deductions.safe.push(cell);
+                } else {
+                    continue;
+                }
+                deductions.solutions.insert(cell, count);
+            }
+        }
+        Ok(deductions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::tests::wall;
+
+    #[test]
+    fn test_one_two_one_has_one_layout() {
+        let deductions = Frontier::new(&wall(&[1, 2, 1])).deductions().unwrap();
+        assert_eq!(deductions.solutions[&Posn::new(0, 0)], 1);
+    }
+
+    #[test]
+    fn test_independent_components() {
+        let mut constraints = wall(&[1, 2, 1]);
+        constraints.push(Constraint::new(vec![Posn::new(5, 5), Posn::new(5, 6)], 1));
+        constraints.push(Constraint::new(vec![Posn::new(5, 6)], 0));
+
+        let components = components(&constraints);
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].cells.len(), 5);
+
+        let deductions = Frontier::new(&constraints).deductions().unwrap();
+        assert!(deductions.mines.contains(&Posn::new(5, 5)));
+        assert!(deductions.safe.contains(&Posn::new(5, 6)));
+    }
+
+    #[test]
+    fn test_solution_counts() {
+        let constraints = vec![Constraint::new(vec![Posn::new(0, 0), Posn::new(0, 1), Posn::new(0, 2)], 1)];
+        let solutions = components(&constraints)[0].enumerate().unwrap();
+        assert_eq!(solutions.count(), 3);
+        assert_eq!(solutions.by_mines[1].mine_counts, vec![1, 1, 1]);
+        assert_eq!(solutions.by_mines[0].count, 0);
+    }
+}
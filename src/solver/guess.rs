@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use crate::posn::Posn;
+use crate::solver::Constraint;
+
+// probabilities this close to each other count as equally good guesses
+const PROBABILITY_EPSILON: f64 = 1e-9;
+
+// how much riskier than the safest cell a guess may be to win on its other merits
+const RISK_TOLERANCE: f64 = 0.02;
+
+const ZERO_WEIGHT: f64 = 0.5;
+const PROGRESS_WEIGHT: f64 = 0.25;
+
+// what a policy can look at when no cell is certain
+pub struct GuessContext<'a> {
+    pub rows: usize,
+    pub cols: usize,
+    // every blank cell with its chance of holding a mine
+    pub probabilities: &'a HashMap<Posn, f64>,
+    pub constraints: &'a [Constraint],
+    pub flagged: &'a HashSet<Posn>,
+}
+
+// picks the blank cell to click when the deductions run dry,
+// any randomness comes from the given rng so a seed reproduces every pick
+pub trait GuessPolicy {
+    fn name(&self) -> &'static str;
+
+    fn choose(&mut self, context: &GuessContext, rng: &mut StdRng) -> Option<Posn>;
+}
+
+// one of the cells at random, sorted first since hash maps iterate in a different order every run
+fn pick(cells: impl Iterator<Item = Posn>, rng: &mut StdRng) -> Option<Posn> {
+    let mut cells: Vec<Posn> = cells.collect();
+    cells.sort_by_key(|posn| (posn.row, posn.col));
+    cells.choose(rng).copied()
+}
+
+// lowest probability of being a bomb, ties broken at random
+pub struct MinProbability;
+
+impl GuessPolicy for MinProbability {
+    fn name(&self) -> &'static str {
+        "min-probability"
+    }
+
+    fn choose(&mut self, context: &GuessContext, rng: &mut StdRng) -> Option<Posn> {
+        let lowest_prob = lowest(context.probabilities);
+        let candidates = context.probabilities
+            .iter()
+            .filter(|&(_, &prob)| prob <= lowest_prob + PROBABILITY_EPSILON)
+            .map(|(&posn, _)| posn);
+        pick(candidates, rng)
+    }
+}
+
+// near-safest cell that is most likely to open a zero or settle the frontier,
+// corners and edges first when everything else ties
+pub struct Informed;
+
+impl Informed {
+    fn score(&self, posn: Posn, context: &GuessContext, frontier: &HashSet<Posn>) -> f64 {
+        let safe = 1.0 - context.probabilities[&posn];
+        let neighbors = posn.surrounding_in_range(context.rows as i32, context.cols as i32);
+
+        // chance that no neighbor holds a mine, treating cells as independent
+        let zero: f64 = if neighbors.iter().any(|neighbor| context.flagged.contains(neighbor)) {
+            0.0
+        } else {
+            neighbors
+                .iter()
+                .filter_map(|neighbor| context.probabilities.get(neighbor))
+                .map(|prob| 1.0 - prob)
+                .product()
+        };
+
+        // a number inside or next to a frontier component narrows its configurations
+        let progress = if frontier.contains(&posn) {
+            1.0
+        } else if neighbors.iter().any(|neighbor| frontier.contains(neighbor)) {
+            0.5
+        } else {
+            0.0
+        };
+
+        safe * (1.0 + ZERO_WEIGHT * zero + PROGRESS_WEIGHT * progress)
+    }
+}
+
+impl GuessPolicy for Informed {
+    fn name(&self) -> &'static str {
+        "informed"
+    }
+
+    fn choose(&mut self, context: &GuessContext, rng: &mut StdRng) -> Option<Posn> {
+        let lowest_prob = lowest(context.probabilities);
+        let frontier: HashSet<Posn> = context.constraints
+            .iter()
+            .flat_map(|constraint| constraint.cells.iter().copied())
+            .collect();
+
+        let scored: Vec<(Posn, f64, usize)> = context.probabilities
+            .iter()
+            .filter(|&(_, &prob)| prob <= lowest_prob + RISK_TOLERANCE)
+            .map(|(&posn, _)| {
+                let neighbors = posn.surrounding_in_range(context.rows as i32, context.cols as i32).len();
+                (posn, self.score(posn, context, &frontier), neighbors)
+            })
+            .collect();
+
+        let best_score = scored.iter().map(|&(_, score, _)| score).fold(f64::NEG_INFINITY, f64::max);
+        let best: Vec<&(Posn, f64, usize)> = scored
+            .iter()
+            .filter(|&&(_, score, _)| score >= best_score - PROBABILITY_EPSILON)
+            .collect();
+        let fewest_neighbors = best.iter().map(|&&(_, _, neighbors)| neighbors).min()?;
+
+        let candidates = best.into_iter()
+            .filter(|&&(_, _, neighbors)| neighbors == fewest_neighbors)
+            .map(|&(posn, _, _)| posn);
+        pick(candidates, rng)
+    }
+}
+
+fn lowest(probabilities: &HashMap<Posn, f64>) -> f64 {
+    probabilities.values().cloned().fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn choose(policy: &mut dyn GuessPolicy, probabilities: &HashMap<Posn, f64>, constraints: &[Constraint]) -> Option<Posn> {
+        choose_seeded(policy, probabilities, constraints, 0)
+    }
+
+    fn choose_seeded(policy: &mut dyn GuessPolicy, probabilities: &HashMap<Posn, f64>, constraints: &[Constraint], seed: u64) -> Option<Posn> {
+        let flagged = HashSet::new();
+        let context = GuessContext {
+            rows: 5,
+            cols: 5,
+            probabilities,
+            constraints,
+            flagged: &flagged,
+        };
+        policy.choose(&context, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn uniform(prob: f64) -> HashMap<Posn, f64> {
+        (0..5)
+            .flat_map(|row| (0..5).map(move |col| (Posn::new(row, col), prob)))
+            .collect()
+    }
+
+    #[test]
+    fn test_min_probability_picks_lowest() {
+        let mut probabilities = uniform(0.3);
+        probabilities.insert(Posn::new(2, 2), 0.1);
+        assert_eq!(choose(&mut MinProbability, &probabilities, &[]), Some(Posn::new(2, 2)));
+    }
+
+    #[test]
+    fn test_informed_prefers_corners_on_ties() {
+        let chosen = choose(&mut Informed, &uniform(0.2), &[]).unwrap();
+        assert!(chosen.row % 4 == 0 && chosen.col % 4 == 0);
+    }
+
+    #[test]
+    fn test_informed_prefers_progress_on_frontier() {
+        let probabilities = uniform(0.2);
+        let constraints = vec![Constraint::new(vec![Posn::new(2, 2)], 0)];
+        assert_eq!(choose(&mut Informed, &probabilities, &constraints), Some(Posn::new(2, 2)));
+    }
+
+    #[test]
+    fn test_informed_never_takes_much_higher_risk() {
+        let mut probabilities = uniform(0.5);
+        probabilities.insert(Posn::new(2, 2), 0.1);
+        assert_eq!(choose(&mut Informed, &probabilities, &[]), Some(Posn::new(2, 2)));
+    }
+
+    #[test]
+    fn test_same_seed_same_guess() {
+        // every map iterates in its own order, the pick has to follow the seed alone
+        for seed in 0..20 {
+            let first = choose_seeded(&mut MinProbability, &uniform(0.2), &[], seed);
+            for _ in 0..5 {
+                assert_eq!(choose_seeded(&mut MinProbability, &uniform(0.2), &[], seed), first);
+            }
+            assert_eq!(choose_seeded(&mut Informed, &uniform(0.2), &[], seed), choose_seeded(&mut Informed, &uniform(0.2), &[], seed));
+        }
+    }
+
+    #[test]
+    fn test_empty_board_has_no_guess() {
+        assert_eq!(choose(&mut MinProbability, &HashMap::new(), &[]), None);
+        assert_eq!(choose(&mut Informed, &HashMap::new(), &[]), None);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::{Error, Result};
+use crate::solver::Constraint;
+use crate::solver::frontier::{self, Component, Deductions};
+
+// one equation over the cells of a component: the sum of coefficient times mine equals the last entry
+type Row = Vec<i64>;
+
+// what row reduction of the constraint matrix settles, cell values being 0 or 1,
+// in polynomial time and without the enumeration's search
+pub fn solve(constraints: &[Constraint]) -> Result<Deductions> {
+    let mut deductions = Deductions::default();
+    for component in frontier::components(constraints) {
+        let Some(values) = solve_component(&component)? else {
+            continue;
+        };
+        for (index, value) in values.into_iter().enumerate() {
+            match value {
+                Some(true) => deductions.mines.push(component.cells[index]),
+                Some(false) => deductions.safe.push(component.cells[index]),
+                None => {}
+            }
+        }
+    }
+    Ok(deductions)
+}
+
+// the settled cells of a component by local index, None when the numbers grew too large to trust
+fn solve_component(component: &Component) -> Result<Option<Vec<Option<bool>>>> {
+    let cells = component.cells.len();
+    let mut values = vec![None; cells];
+    let mut rows: Vec<Row> = component
+        .constraints()
+        .iter()
+        .map(|(indices, mines)| {
+            let mut row = vec![0; cells + 1];
+            for &index in indices {
+                row[index] = 1;
+            }
+            row[cells] = *mines as i64;
+            row
+        })
+        .collect();
+
+    // every settled cell is substituted and the reduction starts over, which can settle more
+    loop {
+        let Some(()) = reduce(&mut rows) else {
+            return Ok(None);
+        };
+        let mut settled = false;
+        for row in &rows {
+            for (index, value) in bounds(row).map_err(|()| contradiction(component))? {
+                if values[index].is_none() {
+                    values[index] = Some(value);
+                    settled = true;
+                }
+            }
+        }
+        if !settled {
+            return Ok(Some(values));
+        }
+        for row in &mut rows {
+            for (index, value) in values.iter().enumerate() {
+                if let Some(mine) = value {
+                    row[cells] -= row[index] * *mine as i64;
+                    row[index] = 0;
+                }
+            }
+        }
+    }
+}
+
+fn contradiction(component: &Component) -> Error {
+    Error::Contradiction(format!("the numbers around {} cannot all be satisfied", component.cells[0].coords()))
+}
+
+// row echelon form with integer entries: rows are combined by cross multiplication and
+// divided by their gcd, which keeps the entries small on real boards. None on overflow
+fn reduce(rows: &mut Vec<Row>) -> Option<()> {
+    let columns = rows.first().map_or(0, |row| row.len() - 1);
+    let mut pivot_row = 0;
+    for column in 0..columns {
+        let Some(found) = (pivot_row..rows.len()).find(|&row| rows[row][column] != 0) else {
+            continue;
+        };
+        rows.swap(pivot_row, found);
+        let pivot = rows[pivot_row].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index == pivot_row || row[column] == 0 {
+                continue;
+            }
+            let (scale, factor) = (pivot[column], row[column]);
+            for (entry, &pivot_entry) in row.iter_mut().zip(&pivot) {
+                *entry = entry.checked_mul(scale)?.checked_sub(pivot_entry.checked_mul(factor)?)?;
+            }
+            normalize(row);
+        }
+        pivot_row += 1;
+    }
+    // rows of zeros carry nothing, unless their right side is not zero, which bounds reports
+    rows.retain(|row| row.iter().any(|&entry| entry != 0));
+    Some(())
+}
+
+fn normalize(row: &mut Row) {
+    let divisor = row.iter().fold(0, |divisor, &entry| gcd(divisor, entry.abs()));
+    if divisor > 1 {
+        row.iter_mut().for_each(|entry| *entry /= divisor);
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// cells one equation forces on its own: a cell whose coefficient is larger than the room between
+// the total and the smallest or largest sum the other cells can reach has only one possible value
+fn bounds(row: &Row) -> std::result::Result<Vec<(usize, bool)>, ()> {
+    let (coefficients, total) = row.split_at(row.len() - 1);
+    let total = total[0];
+    let lowest: i64 = coefficients.iter().filter(|&&entry| entry < 0).sum();
+    let highest: i64 = coefficients.iter().filter(|&&entry| entry > 0).sum();
+    if total < lowest || total > highest {
+        return Err(());
+    }
+
+    let (above, below) = (total - lowest, highest - total);
+    let mut forced = vec![];
+    for (index, &coefficient) in coefficients.iter().enumerate() {
+        // a mine in a cell with a positive coefficient raises the sum, with a negative one it lowers it
+        let raises = coefficient > 0;
+        if coefficient.abs() > above {
+            forced.push((index, !raises));
+        } else if coefficient.abs() > below {
+            forced.push((index, raises));
+        }
+    }
+    Ok(forced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::posn::Posn;
+    use crate::solver::frontier::Frontier;
+
+    #[test]
+    fn test_bounds() {
+        // a - b = 1 forces a mine and a safe cell
+        assert_eq!(bounds(&vec![1, -1, 1]), Ok(vec![(0, true), (1, false)]));
+        // 2a + b + c = 2 settles nothing on its own
+        assert_eq!(bounds(&vec![2, 1, 1, 2]), Ok(vec![]));
+        // 2a + b = 1 means a is safe, b follows once a is substituted
+        assert_eq!(bounds(&vec![2, 1, 1]), Ok(vec![(0, false)]));
+        assert_eq!(bounds(&vec![1, 1, 3]), Err(()));
+    }
+
+    #[test]
+    fn test_agrees_with_the_enumeration() {
+        // random overlapping runs of cells: whatever row reduction settles, the enumeration settles the same way
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for _ in 0..200 {
+            let mines: Vec<bool> = (0..24).map(|_| rng.gen_bool(0.3)).collect();
+            let constraints: Vec<Constraint> = (0..rng.gen_range(2..8))
+                .map(|_| {
+                    let start = rng.gen_range(0..20);
+                    let cells: Vec<Posn> = (start..start + rng.gen_range(1..5)).map(|c| Posn::new(0, c)).collect();
+                    let count = cells.iter().filter(|posn| mines[posn.col as usize]).count();
+                    Constraint::new(cells, count)
+                })
+                .collect();
+
+            let linear = solve(&constraints).unwrap();
+            let exact = Frontier::new(&constraints).deductions().unwrap();
+            assert!(linear.mines.iter().all(|posn| exact.mines.contains(posn)));
+            assert!(linear.safe.iter().all(|posn| exact.safe.contains(posn)));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::posn::Posn;
+use crate::solver::convolve;
+use crate::solver::frontier::{Deductions, Frontier};
+
+// cells the mine counter settles: a layout of the frontier only counts when the mines it leaves over fit
+// into the blank cells no number touches, which in endgames rules out every layout but a few.
+// the cells no number touches are all mines or all safe when every such layout leaves them the same
+pub fn solve(frontier: &Frontier, unconstrained: &[Posn], mines_left: usize) -> Deductions {
+    let mut deductions = Deductions::default();
+    let frontier_cells = frontier.components().iter().map(|component| component.cells.len()).sum();
+    if !can_settle(frontier_cells, unconstrained.len(), mines_left) {
+        return deductions;
+    }
+    let solved: Vec<_> = frontier.solved().collect();
+
+    // per component, 1 for every mine count it can be met with. one too large to enumerate may use any
+    let possible: Vec<Vec<f64>> = solved
+        .iter()
+        .map(|(component, solutions)| match solutions {
+            Some(solutions) => solutions.by_mines.iter().map(|tally| if tally.count > 0 { 1.0 } else { 0.0 }).collect(),
+            None => vec![1.0; component.cells.len() + 1],
+        })
+        .collect();
+    // only whether a total is possible matters, capping keeps large boards from overflowing
+    let combine = |acc: Vec<f64>, ways: &Vec<f64>| -> Vec<f64> {
+        convolve(&acc, ways).into_iter().map(|ways| ways.min(1.0)).collect()
+    };
+    let fits = |frontier_mines: usize| {
+        mines_left.checked_sub(frontier_mines).is_some_and(|rest| rest <= unconstrained.len())
+    };
+
+    let total = possible.iter().fold(vec![1.0], combine);
+    let totals: Vec<usize> = (0..total.len()).filter(|&mines| total[mines] > 0.0 && fits(mines)).collect();
+    // the counter disagrees with the board, ignore it like the probabilities do
+    if totals.is_empty() {
+        return deductions;
+    }
+
+    for (index, (component, solutions)) in solved.iter().enumerate() {
+        let Some(solutions) = solutions else {
+            continue;
+        };
+        let others = possible
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .fold(vec![1.0], |acc, (_, ways)| combine(acc, ways));
+        let usable: Vec<usize> = (0..solutions.by_mines.len())
+            .filter(|&mines| solutions.by_mines[mines].count > 0)
+            .filter(|&mines| others.iter().enumerate().any(|(other, &ways)| ways > 0.0 && fits(mines + other)))
+            .collect();
+
+        for (cell, &posn) in component.cells.iter().enumerate() {
+            let can_be_mine = usable.iter().any(|&mines| solutions.by_mines[mines].mine_counts[cell] > 0);
+            let can_be_safe = usable.iter().any(|&mines| {
+                let tally = &solutions.by_mines[mines];
+                tally.mine_counts[cell] < tally.count
+            });
+            match (can_be_mine, can_be_safe) {
+                (true, false) => deductions.mines.push(posn),
+                (false, true) => deductions.safe.push(posn),
+                _ => {}
+            }
+        }
+    }
+
+    if totals.iter().all(|&mines| mines_left - mines == unconstrained.len()) {
+        deductions.mines.extend(unconstrained);
+    } else if totals.iter().all(|&mines| mines_left == mines) {
+        deductions.safe.extend(unconstrained);
+    }
+    deductions
+}
+
+// whether the counter can rule out any layout. when the frontier cannot use up the mines left and the cells
+// no number touches can take all of them, every layout fits, which is most of the game on a large board
+fn can_settle(frontier_cells: usize, unconstrained: usize, mines_left: usize) -> bool {
+    frontier_cells >= mines_left || mines_left >= unconstrained
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Constraint;
+
+    fn solve(constraints: &[Constraint], unconstrained: &[Posn], mines_left: usize) -> Deductions {
+        super::solve(&Frontier::new(constraints), unconstrained, mines_left)
+    }
+
+    #[test]
+    fn test_last_mines_fill_the_blanks() {
+        let interior = vec![Posn::new(3, 3), Posn::new(3, 4)];
+        let deductions = solve(&[], &interior, 2);
+        assert_eq!(deductions.mines, interior);
+
+        let deductions = solve(&[], &interior, 0);
+        assert_eq!(deductions.safe, interior);
+    }
+
+    #[test]
+    fn test_count_picks_the_layout() {
+        // a + b = 1 and a + c = 1: either a alone or both b and c, with one mine left only a fits
+        let (a, b, c) = (Posn::new(0, 0), Posn::new(0, 1), Posn::new(1, 0));
+        let constraints = vec![Constraint::new(vec![a, b], 1), Constraint::new(vec![a, c], 1)];
+        let interior = vec![Posn::new(5, 5)];
+
+        let deductions = solve(&constraints, &interior, 1);
+        assert_eq!(deductions.mines, vec![a]);
+        assert_eq!(deductions.safe, vec![b, c, interior[0]]);
+
+        // two mines left: a and the interior cell, or b and c
+        let deductions = solve(&constraints, &interior, 2);
+        assert!(deductions.mines.is_empty() && deductions.safe.is_empty());
+    }
+
+    #[test]
+    fn test_wrong_counter_is_ignored() {
+        let constraints = vec![Constraint::new(vec![Posn::new(0, 0), Posn::new(0, 1)], 2)];
+        let deductions = solve(&constraints, &[], 1);
+        assert!(deductions.mines.is_empty() && deductions.safe.is_empty());
+    }
+
+    #[test]
+    fn test_open_board_is_skipped() {
+        // three frontier cells and ten blank ones cannot run out of room for five mines
+        assert!(!can_settle(3, 10, 5));
+        assert!(can_settle(5, 10, 5));
+        assert!(can_settle(3, 5, 5));
+    }
+}
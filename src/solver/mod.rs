@@ -0,0 +1,103 @@
+use std::ops::{Add, Mul};
+use crate::posn::Posn;
+
+pub mod fifty;
+pub mod frontier;
+pub mod guess;
+pub mod linear;
+pub mod mine_count;
+pub mod opening;
+pub mod probability;
+#[cfg(feature = "sat")]
+pub mod sat;
+
+// a revealed number seen from the solver: exactly `mines` of `cells` are mines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub cells: Vec<Posn>,
+    pub mines: usize,
+}
+
+impl Constraint {
+    pub fn new(cells: Vec<Posn>, mines: usize) -> Self {
+        Constraint { cells, mines }
+    }
+}
+
+// the ways to reach every total taking one count from each side, given the ways to reach each count on its own
+pub fn convolve<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    let mut result = vec![T::default(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] = result[i + j] + x * y;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Error, Result};
+    use crate::solver::frontier::{Deductions, Frontier};
+
+    // a row of hidden cells above a wall of numbers, each number sees three cells
+    pub fn wall(numbers: &[usize]) -> Vec<Constraint> {
+        numbers
+            .iter()
+            .enumerate()
+            .map(|(col, &mines)| Constraint::new((col..col + 3).map(|c| Posn::new(0, c as i32)).collect(), mines))
+            .collect()
+    }
+
+    // in reading order, whatever order the solver found them in
+    pub fn sorted(mut posns: Vec<Posn>) -> Vec<Posn> {
+        posns.sort_by_key(|posn| (posn.row, posn.col));
+        posns
+    }
+
+    fn top_row(cols: &[i32]) -> Vec<Posn> {
+        cols.iter().map(|&col| Posn::new(0, col)).collect()
+    }
+
+    type Solve = fn(&[Constraint]) -> Result<Deductions>;
+
+    // every solver that settles the frontier on its own
+    fn solvers() -> Vec<(&'static str, Solve)> {
+        vec![
+            ("enumeration", |constraints| Frontier::new(constraints).deductions()),
+            ("linear", linear::solve),
+            #[cfg(feature = "sat")]
+            ("sat", |constraints| sat::solve(&frontier::components(constraints))),
+        ]
+    }
+
+    #[test]
+    fn test_every_solver() {
+        let pair = vec![Posn::new(0, 0), Posn::new(0, 1)];
+        // the mines and safe cells of the top row, None for a contradiction
+        let cases = [
+            ("1-2-1", wall(&[1, 2, 1]), Some((vec![1, 3], vec![0, 2, 4]))),
+            ("1-2-2-1", wall(&[1, 2, 2, 1]), Some((vec![2, 3], vec![0, 1, 4, 5]))),
+            ("50/50", vec![Constraint::new(pair.clone(), 1)], Some((vec![], vec![]))),
+            // a 2 next to a 0 over the same two cells
+            ("contradiction", vec![Constraint::new(pair.clone(), 2), Constraint::new(pair, 0)], None),
+        ];
+
+        for (solver, solve) in solvers() {
+            for (case, constraints, expected) in &cases {
+                match (solve(constraints), expected) {
+                    (Ok(deductions), Some((mines, safe))) => {
+                        assert_eq!(sorted(deductions.mines), top_row(mines), "{} mines of {}", solver, case);
+                        assert_eq!(sorted(deductions.safe), top_row(safe), "{} safe cells of {}", solver, case);
+                    }
+                    (Err(Error::Contradiction(_)), None) => {}
+                    (result, _) => panic!("{} on {}: {:?}", solver, case, result),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::Rng;
+use crate::backend::FirstClick;
+use crate::difficulty::Difficulty;
+use crate::posn::Posn;
+
+// win rates in percent of the openings in `concrete`, each out of 20000 offline games played with
+// `bench --compare-openings -n 20000 --seed 1 --difficulty <difficulty>`, with `--zero-start` for zero.
+// gaps under a point are within the noise of that many games, auto takes the best measured one anyway
+const WIN_RATES: [(Difficulty, FirstClick, [f64; 5]); 6] = [
+    (Difficulty::Beginner, FirstClick::Safe, [91.6, 91.6, 87.4, 84.0, 86.4]),
+    (Difficulty::Beginner, FirstClick::Zero, [95.8, 95.8, 96.7, 96.0, 96.6]),
+    (Difficulty::Intermediate, FirstClick::Safe, [76.9, 77.2, 73.7, 71.0, 72.6]),
+    (Difficulty::Intermediate, FirstClick::Zero, [84.4, 84.4, 86.7, 86.6, 86.3]),
+    (Difficulty::Expert, FirstClick::Safe, [38.0, 37.9, 35.9, 34.4, 35.5]),
+    (Difficulty::Expert, FirstClick::Zero, [45.0, 44.7, 47.3, 49.7, 49.3]),
+];
+
+// where the first click of a game goes, before there is anything to deduce from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Opening {
+    /// The opening that won most often on the offline engine for the board's difficulty and first click rule
+    Auto,
+    /// Leave it to the guess policy like any other guess
+    Policy,
+    /// The top left corner, the likeliest square to be a zero
+    Corner,
+    /// The middle of the top edge
+    Edge,
+    /// The middle of the board, the largest area when it opens
+    Center,
+    /// Any square, picked from the seed
+    Random,
+}
+
+impl Opening {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opening::Auto => "auto",
+            Opening::Policy => "policy",
+            Opening::Corner => "corner",
+            Opening::Edge => "edge",
+            Opening::Center => "center",
+            Opening::Random => "random",
+        }
+    }
+
+    // every opening that picks a square itself, or hands it to the policy
+    pub fn concrete() -> [Opening; 5] {
+        [Opening::Policy, Opening::Corner, Opening::Edge, Opening::Center, Opening::Random]
+    }
+
+    // auto turned into the opening with the best measured win rate, the others as they are. a custom board
+    // goes by the standard difficulty closest to it in mine density. an unprotected first click was never
+    // measured, the offline engine only plays those on given layouts, so auto leaves it to the policy
+    pub fn resolve(self, difficulty: Difficulty, first_click: FirstClick) -> Opening {
+        if self != Opening::Auto {
+            return self;
+        }
+        let nearest = Difficulty::standard()
+            .into_iter()
+            .min_by(|a, b| {
+                let distance = |standard: &Difficulty| (standard.density() - difficulty.density()).abs();
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap_or(Difficulty::Expert);
+        WIN_RATES
+            .iter()
+            .find(|&&(measured, click, _)| measured == nearest && click == first_click)
+            .map_or(Opening::Policy, |(_, _, rates)| best(rates))
+    }
+
+    // the square to click first, None when the policy decides
+    pub fn square(&self, rows: usize, cols: usize, rng: &mut StdRng) -> Option<Posn> {
+        let (rows, cols) = (rows as i32, cols as i32);
+        match self {
+            Opening::Auto | Opening::Policy => None,
+            Opening::Corner => Some(Posn::new(0, 0)),
+            Opening::Edge => Some(Posn::new(0, cols / 2)),
+            Opening::Center => Some(Posn::new(rows / 2, cols / 2)),
+            Opening::Random => Some(Posn::new(rng.gen_range(0..rows), rng.gen_range(0..cols))),
+        }
+    }
+}
+
+// the opening with the highest win rate, the earlier one on a tie
+fn best(rates: &[f64; 5]) -> Opening {
+    let mut best = 0;
+    for index in 1..rates.len() {
+        if rates[index] > rates[best] {
+            best = index;
+        }
+    }
+    Opening::concrete()[best]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_squares() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(Opening::Corner.square(16, 30, &mut rng), Some(Posn::new(0, 0)));
+        assert_eq!(Opening::Edge.square(16, 30, &mut rng), Some(Posn::new(0, 15)));
+        assert_eq!(Opening::Center.square(16, 30, &mut rng), Some(Posn::new(8, 15)));
+        assert_eq!(Opening::Policy.square(16, 30, &mut rng), None);
+        let random = Opening::Random.square(16, 30, &mut rng).unwrap();
+        assert!(random.in_range(16, 30));
+    }
+
+    #[test]
+    fn test_concrete_openings_are_kept() {
+        for opening in Opening::concrete() {
+            assert_eq!(opening.resolve(Difficulty::Expert, FirstClick::Zero), opening);
+        }
+    }
+
+    #[test]
+    fn test_every_standard_difficulty_is_measured() {
+        for difficulty in Difficulty::standard() {
+            for first_click in [FirstClick::Safe, FirstClick::Zero] {
+                let rows = WIN_RATES.iter().filter(|&&(measured, click, _)| measured == difficulty && click == first_click);
+                assert_eq!(rows.count(), 1, "{} {}", difficulty, first_click.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_board_follows_the_nearest_density() {
+        // 21 mines on 10x10 are about as dense as expert, 48 on 20x20 as beginner
+        let dense = Difficulty::Custom { rows: 10, cols: 10, mines: 21 };
+        let sparse = Difficulty::Custom { rows: 20, cols: 20, mines: 48 };
+        for first_click in [FirstClick::Safe, FirstClick::Zero] {
+            assert_eq!(Opening::Auto.resolve(dense, first_click), Opening::Auto.resolve(Difficulty::Expert, first_click));
+            assert_eq!(Opening::Auto.resolve(sparse, first_click), Opening::Auto.resolve(Difficulty::Beginner, first_click));
+        }
+    }
+
+    #[test]
+    fn test_unprotected_is_left_to_the_policy() {
+        assert_eq!(Opening::Auto.resolve(Difficulty::Beginner, FirstClick::Unprotected), Opening::Policy);
+    }
+
+    #[test]
+    fn test_ties_go_to_the_earlier_opening() {
+        assert_eq!(best(&[80.0, 82.5, 82.5, 79.0, 81.0]), Opening::Corner);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use crate::posn::Posn;
+use crate::solver::convolve;
+use crate::solver::frontier::{Frontier, Solutions};
+
+// chance of holding a mine for every blank cell on the board
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Probabilities {
+    pub frontier: HashMap<Posn, f64>,
+    // shared by every blank cell that no number touches
+    pub unconstrained: f64,
+}
+
+impl Probabilities {
+    pub fn get(&self, posn: Posn) -> f64 {
+        self.frontier.get(&posn).copied().unwrap_or(self.unconstrained)
+    }
+}
+
+// exact probabilities wherever the frontier can be enumerated: every configuration is weighted by the number of
+// ways to place the remaining mines in the unconstrained cells. components too large to enumerate are estimated
+// and hold their expected number of mines, which the rest of the board has to do without
+pub fn compute(frontier: &Frontier, unconstrained: usize, mines_left: usize) -> Probabilities {
+    let mut probabilities = Probabilities::default();
+    let mut solved: Vec<(&[Posn], &Solutions)> = vec![];
+
+    for (component, solutions) in frontier.solved() {
+        match solutions {
+            Some(solutions) if solutions.count() > 0 => solved.push((&component.cells, solutions)),
+            // components too large to enumerate fall back to the densest number touching them
+            _ => {
+                for (cells, mines) in component.constraints() {
+                    let density = *mines as f64 / cells.len() as f64;
+                    for &cell in cells {
+                        let entry = probabilities.frontier.entry(component.cells[cell]).or_insert(0.0);
+                        *entry = entry.max(density);
+                    }
+                }
+            }
+        }
+    }
+
+    let estimated_mines: f64 = probabilities.frontier.values().sum();
+    let mines_left = mines_left.saturating_sub(estimated_mines.round() as usize);
+
+    let weights: Vec<Vec<f64>> = solved
+        .iter()
+        .map(|(_, solutions)| solutions.by_mines.iter().map(|tally| tally.count as f64).collect())
+        .collect();
+    let total = weights.iter().fold(vec![1.0], |acc, weight| convolve(&acc, weight));
+
+    let mut rest = rest_weights(unconstrained, mines_left, total.len() + 1);
+    let mut norm: f64 = (0..total.len()).map(|s| total[s] * rest[s]).sum();
+    if norm <= 0.0 {
+        // the mine counter disagrees with the board, ignore it
+        rest = vec![1.0; total.len() + 1];
+        norm = total.iter().sum();
+    }
+
+    for (index, (cells, solutions)) in solved.iter().enumerate() {
+        let others = weights
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .fold(vec![1.0], |acc, (_, weight)| convolve(&acc, weight));
+
+        for (cell_index, cell) in cells.iter().enumerate() {
+            let mut weight = 0.0;
+            for (mines, tally) in solutions.by_mines.iter().enumerate() {
+                if tally.mine_counts[cell_index] == 0 {
+                    continue;
+                }
+                let ways: f64 = others
+                    .iter()
+                    .enumerate()
+                    .map(|(other_mines, count)| count * rest.get(mines + other_mines).copied().unwrap_or(0.0))
+                    .sum();
+                weight += tally.mine_counts[cell_index] as f64 * ways;
+            }
+            probabilities.frontier.insert(*cell, weight / norm);
+        }
+    }
+
+    if unconstrained > 0 {
+        let expected: f64 = (0..total.len())
+            .map(|s| total[s] * rest[s] * mines_left.saturating_sub(s) as f64)
+            .sum();
+        probabilities.unconstrained = (expected / norm / unconstrained as f64).clamp(0.0, 1.0);
+    }
+    probabilities
+}
+
+// relative number of ways to put the mines the frontier leaves over into the
+// unconstrained cells, indexed by how many mines the frontier uses
+fn rest_weights(unconstrained: usize, mines_left: usize, len: usize) -> Vec<f64> {
+    let logs: Vec<Option<f64>> = (0..len)
+        .map(|frontier_mines| {
+            let rest = mines_left.checked_sub(frontier_mines)?;
+            (rest <= unconstrained).then(|| ln_choose(unconstrained, rest))
+        })
+        .collect();
+    let max = logs.iter().flatten().cloned().fold(f64::NEG_INFINITY, f64::max);
+    logs.into_iter()
+        .map(|log| log.map_or(0.0, |log| (log - max).exp()))
+        .collect()
+}
+
+fn ln_choose(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).ln()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Constraint;
+
+    fn compute(constraints: &[Constraint], unconstrained: usize, mines_left: usize) -> Probabilities {
+        super::compute(&Frontier::new(constraints), unconstrained, mines_left)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_fifty_fifty() {
+        let constraints = vec![Constraint::new(vec![Posn::new(0, 0), Posn::new(0, 1)], 1)];
+        let probabilities = compute(&constraints, 0, 1);
+        assert!(close(probabilities.get(Posn::new(0, 0)), 0.5));
+        assert!(close(probabilities.get(Posn::new(0, 1)), 0.5));
+    }
+
+    #[test]
+    fn test_mine_count_empties_interior() {
+        let constraints = vec![Constraint::new(vec![Posn::new(0, 0), Posn::new(0, 1)], 1)];
+        let probabilities = compute(&constraints, 5, 1);
+        assert!(close(probabilities.unconstrained, 0.0));
+        assert!(close(probabilities.get(Posn::new(0, 1)), 0.5));
+    }
+
+    #[test]
+    fn test_configurations_weighted_by_remaining_mines() {
+        // a + b = 1 and a + c = 1: either a alone or both b and c
+        let (a, b, c) = (Posn::new(0, 0), Posn::new(0, 1), Posn::new(1, 0));
+        let constraints = vec![Constraint::new(vec![a, b], 1), Constraint::new(vec![a, c], 1)];
+        let probabilities = compute(&constraints, 10, 2);
+        // a alone leaves C(10, 1) = 10 ways, b and c leave C(10, 0) = 1
+        assert!(close(probabilities.get(a), 10.0 / 11.0));
+        assert!(close(probabilities.get(b), 1.0 / 11.0));
+        assert!(close(probabilities.unconstrained, 1.0 / 11.0));
+    }
+
+    #[test]
+    fn test_probabilities_sum_to_mines_left() {
+        let constraints = vec![
+            Constraint::new(vec![Posn::new(0, 0), Posn::new(0, 1), Posn::new(0, 2)], 1),
+            Constraint::new(vec![Posn::new(0, 1), Posn::new(0, 2), Posn::new(0, 3)], 2),
+            Constraint::new(vec![Posn::new(5, 5), Posn::new(5, 6)], 1),
+        ];
+        let probabilities = compute(&constraints, 20, 7);
+        let sum: f64 = probabilities.frontier.values().sum::<f64>() + 20.0 * probabilities.unconstrained;
+        assert!(close(sum, 7.0));
+    }
+
+    #[test]
+    fn test_estimated_components_keep_their_mines() {
+        // a number over 40 cells holding 20 mines is too large to enumerate, half of them are estimated to be mines
+        let constraints = vec![Constraint::new((0..40).map(|col| Posn::new(0, col)).collect(), 20)];
+        let probabilities = compute(&constraints, 10, 25);
+        assert!(close(probabilities.get(Posn::new(0, 0)), 0.5));
+        // 5 of the 25 mines are left for the 10 cells no number touches
+        assert!(close(probabilities.unconstrained, 0.5));
+    }
+}
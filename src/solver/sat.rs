@@ -0,0 +1,214 @@
+use crate::error::{Error, Result};
+use crate::solver::frontier::{Component, Counts, Deductions};
+
+// upper bound on search nodes per component, shared by all of its queries
+const SEARCH_LIMIT: usize = 1 << 22;
+
+// cells every mine layout of the given components agrees on, found by asking a solver for a single layout
+// at a time instead of counting them all. meant for the components the enumeration gives up on
+pub fn solve<'a>(components: impl IntoIterator<Item = &'a Component>) -> Result<Deductions> {
+    let mut deductions = Deductions::default();
+    for component in components {
+        let Some(values) = settle(component)? else {
+            continue;
+        };
+        for (index, value) in values.into_iter().enumerate() {
+            match value {
+                Some(true) => deductions.mines.push(component.cells[index]),
+                Some(false) => deductions.safe.push(component.cells[index]),
+                None => {}
+            }
+        }
+    }
+    Ok(deductions)
+}
+
+// asks whether each cell can be a mine and whether it can be safe. every layout found answers both
+// questions for all of its cells, so most cells never need a query of their own
+fn settle(component: &Component) -> Result<Option<Vec<Option<bool>>>> {
+    let cells = component.cells.len();
+    let mut solver = Solver::new(component);
+    let Some(found) = solver.find(None) else {
+        return Ok(None);
+    };
+    let Some(layout) = found else {
+        return Err(Error::Contradiction(format!(
+            "no mine layout satisfies the numbers around {}",
+            component.cells[0].coords()
+        )));
+    };
+
+    let (mut can_be_mine, mut can_be_safe) = (layout.clone(), layout.iter().map(|&mine| !mine).collect::<Vec<_>>());
+    let mut values = vec![None; cells];
+    for cell in 0..cells {
+        for mine in [true, false] {
+            let seen = if mine { &can_be_mine } else { &can_be_safe };
+            if seen[cell] {
+                continue;
+            }
+            match solver.find(Some((cell, mine))) {
+                None => return Ok(None),
+                Some(Some(layout)) => {
+                    for (index, &mine) in layout.iter().enumerate() {
+                        can_be_mine[index] |= mine;
+                        can_be_safe[index] |= !mine;
+                    }
+                }
+                Some(None) => values[cell] = Some(!mine),
+            }
+        }
+    }
+    Ok(Some(values))
+}
+
+// depth first search over cell values, each number kept as a cardinality constraint over its cells
+// and propagated whenever it has room for no more mines or no more safe cells
+struct Solver<'a> {
+    counts: Counts<'a>,
+    values: Vec<Option<bool>>,
+    // assigned cells in order, undone back to a mark when a branch fails
+    trail: Vec<usize>,
+    // the trail up to here has been propagated
+    propagated: usize,
+    nodes: usize,
+}
+
+impl<'a> Solver<'a> {
+    fn new(component: &'a Component) -> Self {
+        Solver {
+            counts: Counts::new(component),
+            values: vec![None; component.cells.len()],
+            trail: vec![],
+            propagated: 0,
+            nodes: 0,
+        }
+    }
+
+    // a layout with the given cell fixed, Some(None) when there is none. None past the search limit,
+    // the component is then left to the guess like one the enumeration gives up on
+    fn find(&mut self, assumption: Option<(usize, bool)>) -> Option<Option<Vec<bool>>> {
+        let found = match assumption {
+            Some((cell, mine)) => self.assign(cell, mine) && self.search(),
+            None => self.search(),
+        };
+        let layout = self.values.iter().map(|value| value.unwrap_or(false)).collect();
+        self.undo(0);
+        if self.nodes > SEARCH_LIMIT {
+            return None;
+        }
+        Some(found.then_some(layout))
+    }
+
+    fn search(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes > SEARCH_LIMIT || !self.propagate() {
+            return false;
+        }
+        let Some(cell) = self.values.iter().position(Option::is_none) else {
+            return true;
+        };
+        let mark = self.trail.len();
+        for mine in [false, true] {
+            if self.assign(cell, mine) && self.search() {
+                return true;
+            }
+            self.undo(mark);
+        }
+        false
+    }
+
+    // fills in the cells of every constraint that has only one way left to be met
+    fn propagate(&mut self) -> bool {
+        while self.propagated < self.trail.len() {
+            let cell = self.trail[self.propagated];
+            self.propagated += 1;
+            for position in 0..self.counts.constraints_of(cell).len() {
+                let index = self.counts.constraints_of(cell)[position];
+                let Some(mine) = self.counts.forced(index) else {
+                    continue;
+                };
+                let (cells, _) = &self.counts.component().constraints()[index];
+                for &other in cells {
+                    if self.values[other].is_none() && !self.assign(other, mine) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // assigns a cell and reports whether every constraint touching it can still hold
+    fn assign(&mut self, cell: usize, mine: bool) -> bool {
+        self.values[cell] = Some(mine);
+        self.trail.push(cell);
+        self.counts.assign(cell, mine)
+    }
+
+    fn undo(&mut self, mark: usize) {
+        self.propagated = self.propagated.min(mark);
+        while self.trail.len() > mark {
+            let cell = self.trail.pop().unwrap_or_default();
+            let mine = self.values[cell].take() == Some(true);
+            self.counts.unassign(cell, mine);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::posn::Posn;
+    use crate::solver::Constraint;
+    use crate::solver::frontier::{self, Frontier};
+    use crate::solver::tests::{sorted, wall};
+
+    // the numbers of random mine layouts on a small board, as the solver would see them after a few reveals
+    fn random_constraints(rng: &mut StdRng) -> Vec<Constraint> {
+        let (rows, cols) = (5, 6);
+        let mines: Vec<bool> = (0..rows * cols).map(|_| rng.gen_bool(0.25)).collect();
+        let mut constraints = vec![];
+        for row in 0..rows {
+            for col in 0..cols {
+                if mines[row * cols + col] || !rng.gen_bool(0.4) {
+                    continue;
+                }
+                let cells: Vec<Posn> = (row.saturating_sub(1)..(row + 2).min(rows))
+                    .flat_map(|r| (col.saturating_sub(1)..(col + 2).min(cols)).map(move |c| (r, c)))
+                    .filter(|&(r, c)| (r, c) != (row, col))
+                    .map(|(r, c)| Posn::new(r as i32, c as i32))
+                    .collect();
+                let count = cells.iter().filter(|posn| mines[posn.row as usize * cols + posn.col as usize]).count();
+                constraints.push(Constraint::new(cells, count));
+            }
+        }
+        constraints
+    }
+
+    #[test]
+    fn test_agrees_with_the_enumeration() {
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..300 {
+            let constraints = random_constraints(&mut rng);
+            let sat = solve(&frontier::components(&constraints)).unwrap();
+            let exact = Frontier::new(&constraints).deductions().unwrap();
+            assert_eq!(sorted(sat.mines), sorted(exact.mines));
+            assert_eq!(sorted(sat.safe), sorted(exact.safe));
+        }
+    }
+
+    #[test]
+    fn test_component_too_large_to_enumerate() {
+        // a 1-2-1 wall whose last cell is shared with a number over 40 cells holding 20 mines
+        let mut constraints = wall(&[1, 2, 1]);
+        constraints.push(Constraint::new((4..44).map(|c| Posn::new(0, c)).collect(), 20));
+
+        let frontier = Frontier::new(&constraints);
+        assert_eq!(frontier.unenumerated().count(), 1);
+        let deductions = solve(frontier.unenumerated()).unwrap();
+        assert_eq!(sorted(deductions.mines), vec![Posn::new(0, 1), Posn::new(0, 3)]);
+        assert_eq!(sorted(deductions.safe), vec![Posn::new(0, 0), Posn::new(0, 2), Posn::new(0, 4)]);
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use serde_json::{json, Value};
+use tokio::time::Duration;
+use crate::backend::Status;
+
+const HISTOGRAM_BUCKETS: usize = 10;
+
+// why a game was lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LossCause {
+    // the fatal click was a guess, nothing certain was left
+    Guess,
+    // the fatal click was supposed to be safe, the solver is wrong somewhere
+    Deduction,
+}
+
+impl LossCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LossCause::Guess => "guess",
+            LossCause::Deduction => "deduction",
+        }
+    }
+}
+
+// what happened in a single game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameReport {
+    pub status: Status,
+    pub duration: Duration,
+    pub guesses: usize,
+    // reveals, flags and chords sent to the game
+    pub clicks: usize,
+    pub loss: Option<LossCause>,
+    // share of the safe cells that were opened, 1.0 for a won game
+    pub progress: f64,
+}
+
+impl GameReport {
+    pub fn won(&self) -> bool {
+        self.status == Status::Won
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "status": self.status.name(),
+            "seconds": self.duration.as_secs_f64(),
+            "guesses": self.guesses,
+            "clicks": self.clicks,
+            "loss": self.loss.map(|cause| cause.name()),
+            "progress": self.progress,
+        })
+    }
+}
+
+// aggregate of many games
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub reports: Vec<GameReport>,
+}
+
+impl Summary {
+    pub fn add(&mut self, report: GameReport) {
+        self.reports.push(report);
+    }
+
+    pub fn games(&self) -> usize {
+        self.reports.len()
+    }
+
+    pub fn wins(&self) -> usize {
+        self.reports.iter().filter(|report| report.won()).count()
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.reports.is_empty() {
+            return 0.0;
+        }
+        self.wins() as f64 / self.games() as f64
+    }
+
+    // completion times of the won games, shortest first
+    fn completion_times(&self) -> Vec<Duration> {
+        let mut times: Vec<Duration> = self.reports
+            .iter()
+            .filter(|report| report.won())
+            .map(|report| report.duration)
+            .collect();
+        times.sort();
+        times
+    }
+
+    pub fn average_time(&self) -> Option<Duration> {
+        let times = self.completion_times();
+        if times.is_empty() {
+            return None;
+        }
+        Some(times.iter().sum::<Duration>() / times.len() as u32)
+    }
+
+    pub fn median_time(&self) -> Option<Duration> {
+        let times = self.completion_times();
+        match times.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(times[len / 2]),
+            len => Some((times[len / 2 - 1] + times[len / 2]) / 2),
+        }
+    }
+
+    pub fn average_guesses(&self) -> f64 {
+        if self.reports.is_empty() {
+            return 0.0;
+        }
+        self.reports.iter().map(|report| report.guesses).sum::<usize>() as f64 / self.games() as f64
+    }
+
+    pub fn average_clicks(&self) -> f64 {
+        if self.reports.is_empty() {
+            return 0.0;
+        }
+        self.reports.iter().map(|report| report.clicks).sum::<usize>() as f64 / self.games() as f64
+    }
+
+    pub fn losses(&self, cause: LossCause) -> usize {
+        self.reports.iter().filter(|report| report.loss == Some(cause)).count()
+    }
+
+    // how far lost games got, in buckets of 10% of the safe cells
+    pub fn progress_histogram(&self) -> [usize; HISTOGRAM_BUCKETS] {
+        let mut histogram = [0; HISTOGRAM_BUCKETS];
+        for report in self.reports.iter().filter(|report| !report.won()) {
+            let bucket = (report.progress * HISTOGRAM_BUCKETS as f64) as usize;
+            histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+        }
+        histogram
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seconds = |time: Option<Duration>| time.map_or("-".to_string(), |time| format!("{:.3}s", time.as_secs_f64()));
+
+        writeln!(f, "games played:    {}", self.games())?;
+        writeln!(f, "games won:       {} ({:.1}%)", self.wins(), 100.0 * self.win_rate())?;
+        writeln!(f, "completion time: average {}, median {}", seconds(self.average_time()), seconds(self.median_time()))?;
+        writeln!(f, "guesses:         {:.2} per game", self.average_guesses())?;
+        writeln!(f, "clicks:          {:.1} per game", self.average_clicks())?;
+        writeln!(
+            f,
+            "losses:          {} on a guess, {} on a deduction",
+            self.losses(LossCause::Guess),
+            self.losses(LossCause::Deduction)
+        )?;
+
+        writeln!(f, "progress of lost games:")?;
+        let histogram = self.progress_histogram();
+        let largest = histogram.iter().copied().max().unwrap_or(0).max(1);
+        for (bucket, &count) in histogram.iter().enumerate() {
+            let bar = "#".repeat((count * 40).div_ceil(largest));
+            writeln!(f, "  {:>3}-{:>3}% {:>5} {}", bucket * 10, bucket * 10 + 10, count, bar)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(won: bool, millis: u64, guesses: usize, progress: f64) -> GameReport {
+        GameReport {
+            status: if won { Status::Won } else { Status::Lost },
+            duration: Duration::from_millis(millis),
+            guesses,
+            clicks: 10 * guesses,
+            loss: if won { None } else if guesses > 0 { Some(LossCause::Guess) } else { Some(LossCause::Deduction) },
+            progress,
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = Summary::default();
+        summary.add(report(true, 300, 1, 1.0));
+        summary.add(report(true, 100, 0, 1.0));
+        summary.add(report(true, 200, 2, 1.0));
+        summary.add(report(false, 50, 1, 0.05));
+        summary.add(report(false, 80, 0, 0.55));
+
+        assert_eq!(summary.games(), 5);
+        assert_eq!(summary.wins(), 3);
+        assert!((summary.win_rate() - 0.6).abs() < 1e-9);
+        assert_eq!(summary.average_time(), Some(Duration::from_millis(200)));
+        assert_eq!(summary.median_time(), Some(Duration::from_millis(200)));
+        assert!((summary.average_guesses() - 0.8).abs() < 1e-9);
+        assert!((summary.average_clicks() - 8.0).abs() < 1e-9);
+        assert_eq!(summary.losses(LossCause::Guess), 1);
+        assert_eq!(summary.losses(LossCause::Deduction), 1);
+        assert_eq!(summary.progress_histogram(), [1, 0, 0, 0, 0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_median_of_even_count() {
+        let mut summary = Summary::default();
+        summary.add(report(true, 100, 0, 1.0));
+        summary.add(report(true, 200, 0, 1.0));
+        assert_eq!(summary.median_time(), Some(Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        let summary = Summary::default();
+        assert_eq!(summary.win_rate(), 0.0);
+        assert_eq!(summary.average_time(), None);
+        assert!(summary.to_string().contains("games played:    0"));
+    }
+}